@@ -7,6 +7,7 @@ struct PostProcessSettings {
 	intensity: f32,
 	scanline_freq: f32,
 	line_intensity: f32,
+	viewport: vec4<f32>,
 #ifdef SIXTEEN_BYTE_ALIGNMENT
 	_webgl2_padding: vec3<f32>
 #endif
@@ -15,7 +16,8 @@ struct PostProcessSettings {
 
 @fragment
 fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
-	let uv = in.uv;
+	// Only the letterboxed viewport is drawn, the bars either side stay black
+	let uv = (in.uv - settings.viewport.xy) / settings.viewport.zw;
 
 	let centered_uv = uv * 2.0 - vec2<f32>(1.0);
 	let k = settings.intensity;
@@ -26,7 +28,7 @@ fn fragment(in: FullscreenVertexOutput) -> @location(0) vec4<f32> {
 		return vec4<f32>(0.0, 0.0, 0.0, 1.0);
 	}
 
-	let color = textureSample(screen_texture, texture_sampler, distorted_uv).rgb;
+	let color = textureSample(screen_texture, texture_sampler, settings.viewport.xy + distorted_uv * settings.viewport.zw).rgb;
 
 	let scanline = 1.0 - settings.line_intensity * sin(distorted_uv.y * settings.scanline_freq * 3.14159);
 	let final_color = color * scanline;
@@ -0,0 +1,64 @@
+//! Plays the game without a window, for balance runs and regression checks
+//!
+//! `cargo run --example headless -- path/to/last.replay` plays back a recorded run, the game keeps its latest in its data directory
+//! `cargo run --example headless -- 20` plays 20 seeds with a simple scripted player
+
+use std::f32::consts::TAU;
+use std::process::ExitCode;
+
+use bevy::math::Vec2;
+use mole_rancher_demake::headless::{simulate, InputScript, PlayerInput, Replay, RunSummary};
+
+/// Ten minutes of play at 60 ticks per second
+const MAX_TICKS: u64 = 60 * 60 * 10;
+
+/// Circles the reactor and swings once a second
+fn circling_player(tick: u64) -> PlayerInput {
+	let angle = tick as f32 / 240.0 * TAU;
+	PlayerInput {
+		target: Some(Vec2::from_angle(angle) * 250.0),
+		swing: tick % 60 < 40,
+		..Default::default()
+	}
+}
+
+fn print_summary(summary: &RunSummary) {
+	println!(
+		"seed {:>20}  ticks {:>6}  score {:>6}  time {:>8.2}s  {}",
+		summary.seed,
+		summary.ticks,
+		summary.score,
+		summary.time_survived,
+		if summary.melted_down {"melted down"} else if summary.died {"died"} else {"survived"},
+	);
+}
+
+fn main() -> ExitCode {
+	let arg = std::env::args().nth(1).unwrap_or_else(|| "10".to_string());
+
+	let scripts = if let Ok(runs) = arg.parse::<u64>() {
+		(0..runs).map(|seed| InputScript::Scripted { seed, input: circling_player }).collect()
+	} else {
+		let replay = std::fs::read(&arg)
+			.map_err(|error| error.to_string())
+			.and_then(|bytes| Replay::from_bytes(&bytes).map_err(|error| error.to_string()));
+		match replay {
+			Ok(replay) => vec![InputScript::Replay(replay)],
+			Err(error) => {
+				eprintln!("Could not read {arg}: {error}");
+				return ExitCode::FAILURE;
+			}
+		}
+	};
+
+	for script in scripts {
+		match simulate(script, MAX_TICKS) {
+			Ok(summary) => print_summary(&summary),
+			Err(error) => {
+				eprintln!("{error}");
+				return ExitCode::FAILURE;
+			}
+		}
+	}
+	ExitCode::SUCCESS
+}
@@ -0,0 +1,434 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::{
+	prelude::*,
+	input::InputSystem,
+};
+use serde::{Deserialize, Serialize};
+
+use crate::input::ControlScheme;
+use crate::loading::TextureAssets;
+use crate::save;
+use crate::ui::{button, button_label, spawn_screen_background, ButtonColors};
+use crate::GameState;
+
+const BINDINGS_SAVE: &str = "bindings.ron";
+/// How many inputs can be bound to each action
+pub const BINDING_SLOTS: usize = 3;
+/// Pressed while rebinding to leave a slot empty, so it can't be bound itself
+const CLEAR_KEY: KeyCode = KeyCode::Backspace;
+
+pub struct ActionsPlugin;
+
+/// This plugin turns the bound keys, mouse buttons and gamepad buttons into actions every frame,
+/// loads and saves the bindings, and runs the screen for remapping them
+impl Plugin for ActionsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<Bindings>()
+			.init_resource::<ActionState>()
+			.init_resource::<Rebinding>()
+			.add_systems(Startup, load_bindings)
+			.add_systems(PreUpdate, update_actions.after(InputSystem))
+			.add_systems(OnEnter(GameState::Bindings), setup_bindings)
+			.add_systems(Update, (capture_binding, click_bindings_button, update_binding_labels).chain().run_if(in_state(GameState::Bindings)))
+			.add_systems(OnExit(GameState::Bindings), (save_bindings, cleanup_bindings))
+			;
+	}
+}
+
+/// Something the player can do, gameplay reads these rather than devices
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Action {
+	MoveUp,
+	MoveDown,
+	MoveLeft,
+	MoveRight,
+	AimUp,
+	AimDown,
+	AimLeft,
+	AimRight,
+	Swing,
+	Pause,
+}
+
+impl Action {
+	pub const ALL: [Action; 10] = [
+		Action::MoveUp,
+		Action::MoveDown,
+		Action::MoveLeft,
+		Action::MoveRight,
+		Action::AimUp,
+		Action::AimDown,
+		Action::AimLeft,
+		Action::AimRight,
+		Action::Swing,
+		Action::Pause,
+	];
+
+	fn label(self) -> &'static str {
+		match self {
+			Action::MoveUp => "MOVE UP",
+			Action::MoveDown => "MOVE DOWN",
+			Action::MoveLeft => "MOVE LEFT",
+			Action::MoveRight => "MOVE RIGHT",
+			Action::AimUp => "AIM UP",
+			Action::AimDown => "AIM DOWN",
+			Action::AimLeft => "AIM LEFT",
+			Action::AimRight => "AIM RIGHT",
+			Action::Swing => "SWING",
+			Action::Pause => "PAUSE",
+		}
+	}
+}
+
+/// A single input that can trigger an action
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Binding {
+	Key(KeyCode),
+	Mouse(MouseButton),
+	Gamepad(GamepadButton),
+}
+
+impl Binding {
+	/// The control scheme a player pressing this is probably using
+	fn scheme(self) -> ControlScheme {
+		match self {
+			Binding::Key(_) => ControlScheme::Keyboard,
+			Binding::Mouse(_) => ControlScheme::Mouse,
+			Binding::Gamepad(_) => ControlScheme::Gamepad,
+		}
+	}
+
+	fn label(self) -> String {
+		match self {
+			Binding::Key(key) => {
+				let name = format!("{key:?}");
+				let name = name.strip_prefix("Key").or_else(|| name.strip_prefix("Digit")).unwrap_or(&name);
+				name.to_uppercase()
+			}
+			Binding::Mouse(button) => format!("MOUSE {button:?}").to_uppercase(),
+			Binding::Gamepad(button) => {
+				let name = match button {
+					GamepadButton::South => "A".to_string(),
+					GamepadButton::East => "B".to_string(),
+					GamepadButton::West => "X".to_string(),
+					GamepadButton::North => "Y".to_string(),
+					GamepadButton::LeftTrigger => "LB".to_string(),
+					GamepadButton::LeftTrigger2 => "LT".to_string(),
+					GamepadButton::RightTrigger => "RB".to_string(),
+					GamepadButton::RightTrigger2 => "RT".to_string(),
+					GamepadButton::LeftThumb => "LS".to_string(),
+					GamepadButton::RightThumb => "RS".to_string(),
+					GamepadButton::DPadUp => "UP".to_string(),
+					GamepadButton::DPadDown => "DOWN".to_string(),
+					GamepadButton::DPadLeft => "LEFT".to_string(),
+					GamepadButton::DPadRight => "RIGHT".to_string(),
+					button => format!("{button:?}").to_uppercase(),
+				};
+				format!("PAD {name}")
+			}
+		}
+	}
+}
+
+/// Which inputs trigger each action, saved between sessions
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct Bindings(pub BTreeMap<Action, [Option<Binding>; BINDING_SLOTS]>);
+
+impl Default for Bindings {
+	fn default() -> Self {
+		use Binding::*;
+		Bindings(BTreeMap::from([
+			(Action::MoveUp, [Some(Key(KeyCode::KeyW)), None, Some(Gamepad(GamepadButton::DPadUp))]),
+			(Action::MoveDown, [Some(Key(KeyCode::KeyS)), None, Some(Gamepad(GamepadButton::DPadDown))]),
+			(Action::MoveLeft, [Some(Key(KeyCode::KeyA)), None, Some(Gamepad(GamepadButton::DPadLeft))]),
+			(Action::MoveRight, [Some(Key(KeyCode::KeyD)), None, Some(Gamepad(GamepadButton::DPadRight))]),
+			(Action::AimUp, [Some(Key(KeyCode::ArrowUp)), None, None]),
+			(Action::AimDown, [Some(Key(KeyCode::ArrowDown)), None, None]),
+			(Action::AimLeft, [Some(Key(KeyCode::ArrowLeft)), None, None]),
+			(Action::AimRight, [Some(Key(KeyCode::ArrowRight)), None, None]),
+			(Action::Swing, [Some(Mouse(MouseButton::Left)), Some(Key(KeyCode::Space)), Some(Gamepad(GamepadButton::RightTrigger2))]),
+			(Action::Pause, [Some(Key(KeyCode::Escape)), None, Some(Gamepad(GamepadButton::Start))]),
+		]))
+	}
+}
+
+impl Bindings {
+	/// Binds a slot, taking the input away from anything else it was bound to
+	fn set(&mut self, action: Action, slot: usize, binding: Option<Binding>) {
+		if binding.is_some() {
+			for bound in self.0.values_mut().flatten() {
+				if *bound == binding {
+					*bound = None;
+				}
+			}
+		}
+		self.0.entry(action).or_default()[slot] = binding;
+	}
+}
+
+/// Which actions are held this frame, updated before anything else reads input
+#[derive(Resource, Default)]
+pub struct ActionState {
+	pressed: HashSet<Action>,
+	just_pressed: HashSet<Action>,
+	/// Scheme of the last binding pressed this frame, if any
+	just_used: Option<ControlScheme>,
+}
+
+impl ActionState {
+	pub fn pressed(&self, action: Action) -> bool {
+		self.pressed.contains(&action)
+	}
+
+	pub fn just_pressed(&self, action: Action) -> bool {
+		self.just_pressed.contains(&action)
+	}
+
+	pub fn just_used(&self) -> Option<ControlScheme> {
+		self.just_used
+	}
+
+	/// Direction from four actions, normalised so diagonals aren't longer
+	pub fn direction(&self, up: Action, down: Action, left: Action, right: Action) -> Vec2 {
+		let axis = |negative, positive| self.pressed(positive) as i32 as f32 - self.pressed(negative) as i32 as f32;
+		Vec2::new(axis(left, right), axis(down, up)).normalize_or_zero()
+	}
+}
+
+/// The slot waiting for an input on the bindings screen
+#[derive(Resource, Default)]
+struct Rebinding(Option<(Action, usize)>);
+
+#[derive(Component, Clone)]
+struct BindingsScreen;
+
+#[derive(Component)]
+struct BindingsHint;
+
+#[derive(Component, Clone, Copy)]
+enum BindingsButton {
+	Slot(Action, usize),
+	Reset,
+	Back,
+}
+
+fn load_bindings(mut bindings: ResMut<Bindings>) {
+	match save::load::<Bindings>(BINDINGS_SAVE) {
+		Ok(Some(saved)) => {
+			// Actions added since the file was saved keep their default bindings
+			for (action, slots) in saved.0 {
+				bindings.0.insert(action, slots);
+			}
+		}
+		Ok(None) => (),
+		Err(error) => warn!("Could not load bindings, using the defaults: {error}"),
+	}
+}
+
+fn save_bindings(bindings: Res<Bindings>) {
+	if let Err(error) = save::store(BINDINGS_SAVE, &*bindings) {
+		warn!("Could not save bindings: {error}");
+	}
+}
+
+pub fn update_actions(
+	mut actions: ResMut<ActionState>,
+	bindings: Res<Bindings>,
+	keyboard: Res<ButtonInput<KeyCode>>,
+	mouse: Res<ButtonInput<MouseButton>>,
+	gamepads: Query<&Gamepad>,
+) {
+	actions.pressed.clear();
+	actions.just_pressed.clear();
+	actions.just_used = None;
+	for (&action, slots) in bindings.0.iter() {
+		for &binding in slots.iter().flatten() {
+			let (pressed, just_pressed) = match binding {
+				Binding::Key(key) => (keyboard.pressed(key), keyboard.just_pressed(key)),
+				Binding::Mouse(button) => (mouse.pressed(button), mouse.just_pressed(button)),
+				Binding::Gamepad(button) => (
+					gamepads.iter().any(|gamepad| gamepad.pressed(button)),
+					gamepads.iter().any(|gamepad| gamepad.just_pressed(button)),
+				),
+			};
+			if pressed {
+				actions.pressed.insert(action);
+			}
+			if just_pressed {
+				actions.just_pressed.insert(action);
+				actions.just_used = Some(binding.scheme());
+			}
+		}
+	}
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, width: f32, label: &str, marker: BindingsButton) {
+	parent
+		.spawn(button(Node {width: Val::Px(width), height: Val::Px(40.0), ..default()}, marker))
+		.with_child(button_label(label, 20.0));
+}
+
+fn setup_bindings(
+	mut commands: Commands,
+	mut rebinding: ResMut<Rebinding>,
+	textures: Res<TextureAssets>,
+) {
+	rebinding.0 = None;
+	spawn_screen_background(&mut commands, &textures, BindingsScreen);
+	commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			width: Val::Percent(100.0),
+			height: Val::Percent(100.0),
+			flex_direction: FlexDirection::Column,
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			row_gap: Val::Px(8.0),
+			..default()
+		},
+		BindingsScreen,
+	))
+	.with_children(|children| {
+		children.spawn((
+			Text::new("BINDINGS"),
+			TextFont {
+				font_size: 50.0,
+				..default()
+			},
+			TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+		));
+		children.spawn((
+			Text::default(),
+			TextFont {
+				font_size: 20.0,
+				..default()
+			},
+			TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+			BindingsHint,
+		));
+		for action in Action::ALL {
+			children
+				.spawn((
+					Node {
+						width: Val::Px(760.0),
+						padding: UiRect::axes(Val::Px(12.0), Val::Px(4.0)),
+						align_items: AlignItems::Center,
+						column_gap: Val::Px(10.0),
+						..default()
+					},
+					BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.6)),
+				))
+				.with_children(|row| {
+					row.spawn((
+						Text::new(action.label()),
+						TextFont {
+							font_size: 25.0,
+							..default()
+						},
+						TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+						Node {
+							flex_grow: 1.0,
+							..default()
+						},
+					));
+					for slot in 0..BINDING_SLOTS {
+						spawn_button(row, 170.0, "", BindingsButton::Slot(action, slot));
+					}
+				});
+		}
+		children
+			.spawn(Node {
+				column_gap: Val::Px(16.0),
+				..default()
+			})
+			.with_children(|row| {
+				spawn_button(row, 202.0, "RESET", BindingsButton::Reset);
+				spawn_button(row, 202.0, "BACK", BindingsButton::Back);
+			});
+	});
+}
+
+/// Binds the first input pressed while a slot is waiting for one
+fn capture_binding(
+	mut rebinding: ResMut<Rebinding>,
+	mut bindings: ResMut<Bindings>,
+	keyboard: Res<ButtonInput<KeyCode>>,
+	mouse: Res<ButtonInput<MouseButton>>,
+	gamepads: Query<&Gamepad>,
+) {
+	let Some((action, slot)) = rebinding.0 else {return};
+	let pressed = keyboard.get_just_pressed().next().map(|&key| Binding::Key(key))
+		.or_else(|| mouse.get_just_pressed().next().map(|&button| Binding::Mouse(button)))
+		.or_else(|| gamepads.iter().find_map(|gamepad| gamepad.get_just_pressed().next().map(|&button| Binding::Gamepad(button))));
+	let Some(binding) = pressed else {return};
+	bindings.set(action, slot, Some(binding).filter(|binding| *binding != Binding::Key(CLEAR_KEY)));
+	rebinding.0 = None;
+}
+
+fn click_bindings_button(
+	mut next_state: ResMut<NextState<GameState>>,
+	mut rebinding: ResMut<Rebinding>,
+	mut bindings: ResMut<Bindings>,
+	mut interaction_query: Query<
+		(&Interaction, &mut BackgroundColor, &ButtonColors, &BindingsButton),
+		(Changed<Interaction>, With<Button>),
+	>,
+) {
+	// A click that was just captured as a binding shouldn't also press the button under the cursor
+	let just_captured = rebinding.is_changed();
+	for (interaction, mut color, button_colors, button) in &mut interaction_query {
+		match *interaction {
+			Interaction::Pressed if just_captured => (),
+			Interaction::Pressed => match *button {
+				BindingsButton::Slot(action, slot) => rebinding.0 = Some((action, slot)),
+				BindingsButton::Reset => *bindings = Bindings::default(),
+				BindingsButton::Back => next_state.set(GameState::Settings),
+			},
+			Interaction::Hovered => {
+				*color = button_colors.hovered.into();
+			}
+			Interaction::None => {
+				*color = button_colors.normal.into();
+			}
+		}
+	}
+}
+
+fn update_binding_labels(
+	rebinding: Res<Rebinding>,
+	bindings: Res<Bindings>,
+	button_query: Query<(&BindingsButton, &Children)>,
+	mut hint_query: Query<&mut Text, With<BindingsHint>>,
+	mut text_query: Query<&mut Text, Without<BindingsHint>>,
+) {
+	if !rebinding.is_changed() && !bindings.is_changed() {
+		return;
+	}
+	for mut hint in hint_query.iter_mut() {
+		hint.0 = match rebinding.0 {
+			Some((action, _)) => format!("PRESS AN INPUT FOR {}, BACKSPACE TO CLEAR", action.label()),
+			None => "CLICK A SLOT TO REBIND IT".to_string(),
+		};
+	}
+	for (button, children) in button_query.iter() {
+		let BindingsButton::Slot(action, slot) = *button else {continue};
+		let label = if rebinding.0 == Some((action, slot)) {
+			"...".to_string()
+		} else {
+			bindings.0.get(&action).and_then(|slots| slots[slot]).map_or("-".to_string(), Binding::label)
+		};
+		for &child in children {
+			if let Ok(mut text) = text_query.get_mut(child) {
+				text.0 = label.clone();
+			}
+		}
+	}
+}
+
+fn cleanup_bindings(mut commands: Commands, screen: Query<Entity, With<BindingsScreen>>) {
+	for entity in screen.iter() {
+		commands.entity(entity).despawn();
+	}
+}
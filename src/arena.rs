@@ -0,0 +1,57 @@
+use bevy::prelude::*;
+
+/// The room everything is kept inside, shared by every wall collision so they all agree
+#[derive(Resource, Clone, Copy, PartialEq, Debug)]
+pub struct Arena {
+	/// Size of the whole room, walls included, centred on the origin
+	pub size: Vec2,
+	pub walls: Walls,
+}
+
+/// Thickness of each wall, measured inwards from the edge of the room
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Walls {
+	pub left: f32,
+	pub right: f32,
+	pub top: f32,
+	pub bottom: f32,
+}
+
+impl Default for Arena {
+	fn default() -> Self {
+		// The walls drawn in `background.png`
+		Arena {
+			size: Vec2::new(1080.0, 810.0),
+			walls: Walls {
+				left: 47.0,
+				right: 43.0,
+				top: 130.0,
+				bottom: 76.0,
+			},
+		}
+	}
+}
+
+impl Arena {
+	/// The open floor between the walls
+	pub fn floor(&self) -> Rect {
+		let half_size = self.size / 2.0;
+		Rect::new(
+			-half_size.x + self.walls.left,
+			-half_size.y + self.walls.bottom,
+			half_size.x - self.walls.right,
+			half_size.y - self.walls.top,
+		)
+	}
+
+	/// Where the centre of a circle can be without it overlapping a wall
+	pub fn bounds(&self, radius: f32) -> Rect {
+		self.floor().inflate(-radius)
+	}
+
+	/// Moves the centre of a circle out of any wall it overlaps
+	pub fn clamp(&self, position: Vec2, radius: f32) -> Vec2 {
+		let bounds = self.bounds(radius);
+		position.clamp(bounds.min, bounds.max)
+	}
+}
@@ -1,25 +1,100 @@
 use crate::loading::AudioAssets;
+use crate::settings::Settings;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_kira_audio::prelude::*;
 
 pub struct InternalAudioPlugin;
 
+/// This plugin plays the music and the sound effects gameplay asks for with [`PlaySfx`]
 impl Plugin for InternalAudioPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(AudioPlugin)
-            .add_systems(OnEnter(GameState::Menu), start_audio);
+            .add_systems(OnExit(GameState::Loading), start_audio)
+            .add_systems(Update, (
+                play_sfx.run_if(resource_exists::<AudioAssets>),
+                apply_music_volume.run_if(resource_exists::<Music>.and(resource_changed::<Settings>)),
+            ));
     }
 }
 
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Sfx {
+	WindUp,
+	BatSwing,
+	BounceAndCrackle,
+	Ping,
+	RadiationHit,
+}
+
+/// Volume of the music with the music volume setting at full
+const MUSIC_VOLUME: f64 = 0.4;
+
+#[derive(Resource)]
+struct Music(Handle<AudioInstance>);
+
+/// Sent by gameplay to play a sound effect
+/// Gameplay never talks to the audio backend directly so it can run without one
+#[derive(Event, Clone, Copy, Debug)]
+pub struct PlaySfx {
+	pub sfx: Sfx,
+	pub volume: f64,
+	pub playback_rate: f64,
+}
+
 fn start_audio(
+	mut commands: Commands,
 	audio_assets: Res<AudioAssets>, 
 	audio: Res<Audio>,
+	settings: Res<Settings>,
 ) {
 	audio.resume();
-	let _handle = audio
+	let handle = audio
         .play(audio_assets.bgm.clone())
 		.loop_from(57.6)
-        .with_volume(0.4)
+        .with_volume(MUSIC_VOLUME * settings.music_volume as f64)
         .handle();
-}
\ No newline at end of file
+	commands.insert_resource(Music(handle));
+}
+
+fn apply_music_volume(
+	mut audio_instances: ResMut<Assets<AudioInstance>>,
+	music: Res<Music>,
+	settings: Res<Settings>,
+) {
+	if let Some(instance) = audio_instances.get_mut(&music.0) {
+		instance.set_volume(MUSIC_VOLUME * settings.music_volume as f64, AudioTween::default());
+	}
+}
+
+fn play_sfx(
+	mut sfx_events: EventReader<PlaySfx>,
+	mut audio_instances: ResMut<Assets<AudioInstance>>,
+	mut wind_handle: Local<Handle<AudioInstance>>,
+	audio: Res<Audio>,
+	audio_assets: Res<AudioAssets>,
+	settings: Res<Settings>,
+) {
+	for event in sfx_events.read() {
+		let source = match event.sfx {
+			Sfx::WindUp => &audio_assets.wind_up,
+			Sfx::BatSwing => &audio_assets.bat_swing,
+			Sfx::BounceAndCrackle => &audio_assets.bounce_and_crackle,
+			Sfx::Ping => &audio_assets.ping,
+			Sfx::RadiationHit => &audio_assets.radiation_hit,
+		};
+		// Swinging cuts the wind up short
+		if event.sfx == Sfx::BatSwing {
+			if let Some(instance) = audio_instances.get_mut(&*wind_handle) {
+				instance.pause(AudioTween::default());
+			}
+		}
+		let handle = audio.play(source.clone())
+			.with_volume(event.volume * settings.sfx_volume as f64)
+			.with_playback_rate(event.playback_rate)
+			.handle();
+		if event.sfx == Sfx::WindUp {
+			*wind_handle = handle;
+		}
+	}
+}
@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy::render::camera::{ScalingMode, Viewport};
+use bevy::window::PrimaryWindow;
+
+use crate::postprocess::PostProcessSettings;
+use crate::settings::Settings;
+
+/// Size of the world the camera shows, letterboxed into any window
+pub const VIEW_SIZE: Vec2 = Vec2::new(1080.0, 810.0);
+/// Where the camera sits when it isn't shaking
+const CAMERA_REST: Vec3 = Vec3::new(0.0, 0.0, 1000.0);
+/// Largest distance the camera is thrown by a shake, in world units
+const MAX_SHAKE_OFFSET: f32 = 12.0;
+/// Trauma lost per second
+const SHAKE_DECAY: f32 = 1.5;
+
+pub struct CameraPlugin;
+
+/// This plugin spawns the camera, letterboxes it to the window, shakes it when gameplay asks and applies the CRT setting to it
+impl Plugin for CameraPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<Trauma>()
+			.add_systems(Startup, spawn_camera)
+			.add_systems(Update, (
+				letterbox_camera,
+				shake_camera,
+				apply_crt_strength.run_if(resource_changed::<Settings>),
+			))
+			;
+	}
+}
+
+#[derive(Component)]
+pub struct MainCamera;
+
+/// Sent by gameplay to shake the screen, `0.0` to `1.0`
+#[derive(Event, Clone, Copy, Debug)]
+pub struct CameraShake(pub f32);
+
+/// How shaken the camera currently is, the shake grows with its square
+#[derive(Resource, Default)]
+struct Trauma(f32);
+
+/// Converts a position in logical window pixels, like the cursor or a touch, to the world
+/// Positions on the black bars outside the view give `None`
+pub fn window_to_world(camera: &Camera, position: Vec2) -> Option<Vec2> {
+	let viewport = camera.logical_viewport_rect()?;
+	if !viewport.contains(position) {
+		return None;
+	}
+	// Mapped from where the camera rests, so screen shake only moves what is drawn and never the aim
+	// Bevy already takes the viewport's offset into the window off the position
+	camera.viewport_to_world_2d(&GlobalTransform::from_translation(CAMERA_REST), position).ok()
+}
+
+fn spawn_camera(mut commands: Commands) {
+	commands.spawn((
+        Camera2d,
+		Projection::from(OrthographicProjection {
+			scaling_mode: ScalingMode::Fixed {
+				width: VIEW_SIZE.x,
+				height: VIEW_SIZE.y,
+			},
+			..OrthographicProjection::default_2d()
+		}),
+		Transform::from_translation(CAMERA_REST),
+        MainCamera,
+        PostProcessSettings {
+            intensity: 0.025,
+            scanline_freq: 202.5,
+            line_intensity: 0.1,
+            viewport: Vec4::new(0.0, 0.0, 1.0, 1.0),
+            ..default()
+        },
+    ));
+}
+
+/// Fits the largest view with the right aspect ratio into the window, with black bars either side
+/// The UI is scaled along with it, so it is laid out as though the window were always the same size
+fn letterbox_camera(
+	windows: Query<&Window, With<PrimaryWindow>>,
+	mut camera_query: Query<(&mut Camera, &mut PostProcessSettings), With<MainCamera>>,
+	mut ui_scale: ResMut<UiScale>,
+) {
+	let Ok(window) = windows.single() else {return};
+	let window_size = window.physical_size().as_vec2();
+	if window_size.min_element() < 1.0 {
+		return;
+	}
+	let scale = (window_size / VIEW_SIZE).min_element();
+	let size = (VIEW_SIZE * scale).round().max(Vec2::ONE);
+	let position = ((window_size - size) / 2.0).floor();
+
+	for (mut camera, mut post_process) in camera_query.iter_mut() {
+		let unchanged = camera.viewport.as_ref().is_some_and(|viewport| {
+			viewport.physical_position == position.as_uvec2() && viewport.physical_size == size.as_uvec2()
+		});
+		if !unchanged {
+			camera.viewport = Some(Viewport {
+				physical_position: position.as_uvec2(),
+				physical_size: size.as_uvec2(),
+				..default()
+			});
+		}
+		// The post processing pass covers the whole window, so it needs to know where the view is
+		let offset = position / window_size;
+		let extent = size / window_size;
+		let viewport = Vec4::new(offset.x, offset.y, extent.x, extent.y);
+		if post_process.viewport != viewport {
+			post_process.viewport = viewport;
+		}
+	}
+	let scale = scale / window.scale_factor();
+	if ui_scale.0 != scale {
+		ui_scale.0 = scale;
+	}
+}
+
+fn shake_camera(
+	mut shake_events: EventReader<CameraShake>,
+	mut trauma: ResMut<Trauma>,
+	mut camera_query: Query<&mut Transform, With<MainCamera>>,
+	settings: Res<Settings>,
+	time: Res<Time>,
+) {
+	for shake in shake_events.read() {
+		trauma.0 = (trauma.0 + shake.0).clamp(0.0, 1.0);
+	}
+	trauma.0 = (trauma.0 - SHAKE_DECAY * time.delta_secs()).max(0.0);
+
+	let offset = if settings.screen_shake {
+		// Purely cosmetic, so it doesn't draw from the game's random numbers and replays stay the same
+		let direction = Vec2::new(rand::random::<f32>() * 2.0 - 1.0, rand::random::<f32>() * 2.0 - 1.0);
+		direction * MAX_SHAKE_OFFSET * trauma.0 * trauma.0
+	} else {
+		Vec2::ZERO
+	};
+	for mut transform in camera_query.iter_mut() {
+		transform.translation = CAMERA_REST + offset.extend(0.0);
+	}
+}
+
+fn apply_crt_strength(
+	mut camera_query: Query<&mut PostProcessSettings, With<MainCamera>>,
+	settings: Res<Settings>,
+) {
+	for mut post_process in camera_query.iter_mut() {
+		post_process.intensity = 0.025 * settings.crt_strength;
+		post_process.line_intensity = 0.1 * settings.crt_strength;
+	}
+}
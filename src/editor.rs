@@ -0,0 +1,405 @@
+use bevy::{
+	prelude::*,
+	input::mouse::MouseWheel,
+	window::PrimaryWindow,
+};
+use crate::arena::Arena;
+use crate::camera::{window_to_world, MainCamera};
+use crate::levels::{Level, SpawnRecord, Wave};
+use crate::loading::{DataAssets, TextureAssets};
+use crate::registry::{MoleculeKind, MoleculeRegistry};
+use crate::save;
+use crate::weapons::WeaponSet;
+use crate::GameState;
+
+/// Save the editor keeps its level in, in the data directory or browser storage like the rest of the saves
+/// Runs load `levels/default.level.ron`, a level saved here is played by loading it in the editor and pressing Enter
+const EDITOR_LEVEL_SAVE: &str = "custom.level.ron";
+/// Centre of the reactor molecules leave from, matching `spawn_reactor`
+const REACTOR_CENTRE: Vec2 = Vec2::ZERO;
+/// Tracking spawns are previewed aiming at where the player starts a run
+const PLAYER_START: Vec2 = Vec2::new(0.0, 220.0);
+
+pub struct EditorPlugin;
+
+/// This plugin lets designers lay out the timed spawns of a level
+/// Spawns are placed with the mouse at the playhead and previewed by scrubbing the timeline, or played for real from the editor
+impl Plugin for EditorPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(OnEnter(GameState::Editor), setup_editor)
+			.add_systems(Update, (
+				editor_input,
+				editor_playback,
+				resize_reactor,
+				draw_editor,
+				update_editor_text,
+			).chain().run_if(in_state(GameState::Editor)))
+			.add_systems(OnExit(GameState::Editor), cleanup_editor)
+			.add_systems(OnEnter(GameState::Menu), end_play_test)
+			;
+	}
+}
+
+/// Present while the editor's level is being played, those runs aren't scored or recorded
+#[derive(Resource)]
+pub struct PlayTest(Handle<Level>);
+
+impl PlayTest {
+	/// The level runs are played on, the one being tested or else the loaded one
+	pub fn level<'a>(play_test: Option<&'a PlayTest>, data: &'a DataAssets) -> &'a Handle<Level> {
+		play_test.map_or(&data.level, |play_test| &play_test.0)
+	}
+}
+
+/// The level being edited, kept between visits to the editor
+#[derive(Resource)]
+struct EditorState {
+	level: Level,
+	wave: usize,
+	playhead: f32,
+	playing: bool,
+	molecule: MoleculeKind,
+	track_player: bool,
+	status: String,
+}
+
+#[derive(Component)]
+struct Editor;
+
+#[derive(Component)]
+struct EditorText;
+
+/// Preview of the reactor, sized to the level being edited
+#[derive(Component)]
+struct EditorReactor;
+
+fn setup_editor(
+	mut commands: Commands,
+	editor: Option<Res<EditorState>>,
+	levels: Res<Assets<Level>>,
+	data: Res<DataAssets>,
+	textures: Res<TextureAssets>,
+	registry: Res<MoleculeRegistry>,
+) {
+	if editor.is_none() {
+		let level = levels.get(&data.level).expect("Could not find level").clone();
+		commands.insert_resource(EditorState {
+			level,
+			wave: 0,
+			playhead: 0.0,
+			playing: false,
+			molecule: registry.kinds().next().expect("Could not find any molecules"),
+			track_player: false,
+			status: String::new(),
+		});
+	}
+
+	commands.spawn((
+		Sprite {
+			image: textures.hoop.clone(),
+			..default()
+		},
+		Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
+		Editor,
+		EditorReactor,
+	));
+	commands.spawn((
+		Text::new(""),
+		TextFont {
+			font_size: 15.0,
+			..default()
+		},
+		TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Px(10.0),
+			top: Val::Px(10.0),
+			..default()
+		},
+		Editor,
+		EditorText,
+	));
+}
+
+fn cursor_to_world(window: &Window, camera: &Camera) -> Option<Vec2> {
+	window.cursor_position().and_then(|position| window_to_world(camera, position))
+}
+
+fn editor_input(
+	mut commands: Commands,
+	mut next_state: ResMut<NextState<GameState>>,
+	mut editor: ResMut<EditorState>,
+	mut levels: ResMut<Assets<Level>>,
+	mut wheel_events: EventReader<MouseWheel>,
+	registry: Res<MoleculeRegistry>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	camera_query: Query<&Camera, With<MainCamera>>,
+	keys: Res<ButtonInput<KeyCode>>,
+	mouse: Res<ButtonInput<MouseButton>>,
+	time: Res<Time>,
+) {
+	let digits = [
+		KeyCode::Digit0, KeyCode::Digit1, KeyCode::Digit2, KeyCode::Digit3, KeyCode::Digit4,
+		KeyCode::Digit5, KeyCode::Digit6, KeyCode::Digit7, KeyCode::Digit8, KeyCode::Digit9,
+	];
+	for (molecule, digit) in registry.kinds().zip(digits) {
+		if keys.just_pressed(digit) {
+			editor.molecule = molecule;
+		}
+	}
+	if keys.just_pressed(KeyCode::KeyT) {
+		editor.track_player = !editor.track_player;
+	}
+	if keys.just_pressed(KeyCode::Space) {
+		editor.playing = !editor.playing;
+	}
+
+	// Timeline scrubbing
+	let mut scrub = 0.0;
+	if keys.pressed(KeyCode::ArrowLeft) {scrub -= 2.0 * time.delta_secs()};
+	if keys.pressed(KeyCode::ArrowRight) {scrub += 2.0 * time.delta_secs()};
+	for event in wheel_events.read() {
+		scrub += event.y.signum() * 0.25;
+	}
+	if scrub != 0.0 {
+		editor.playing = false;
+		let duration = editor.level.waves[editor.wave].duration;
+		editor.playhead = (editor.playhead + scrub).clamp(0.0, duration);
+	}
+	if keys.just_pressed(KeyCode::Home) {
+		editor.playhead = 0.0;
+	}
+
+	// Wave management
+	if keys.just_pressed(KeyCode::ArrowUp) && editor.wave > 0 {
+		editor.wave -= 1;
+		editor.playhead = 0.0;
+	}
+	if keys.just_pressed(KeyCode::ArrowDown) && editor.wave + 1 < editor.level.waves.len() {
+		editor.wave += 1;
+		editor.playhead = 0.0;
+	}
+	if keys.just_pressed(KeyCode::KeyN) {
+		let index = editor.wave + 1;
+		editor.level.waves.insert(index, Wave { duration: 5.0, spawns: vec![], obstacles: vec![], pickups: vec![] });
+		editor.wave = index;
+		editor.playhead = 0.0;
+	}
+	if keys.just_pressed(KeyCode::Delete) && editor.level.waves.len() > 1 {
+		let index = editor.wave;
+		editor.level.waves.remove(index);
+		editor.wave = index.min(editor.level.waves.len() - 1);
+		editor.playhead = 0.0;
+	}
+	let mut duration_change = 0.0;
+	if keys.just_pressed(KeyCode::Minus) {duration_change -= 0.5};
+	if keys.just_pressed(KeyCode::Equal) {duration_change += 0.5};
+	if duration_change != 0.0 {
+		let index = editor.wave;
+		let wave = &mut editor.level.waves[index];
+		let last_spawn = wave.spawns.last().map_or(0.0, |record| record.time);
+		wave.duration = (wave.duration + duration_change).max(last_spawn).max(0.5);
+		let duration = wave.duration;
+		editor.playhead = editor.playhead.min(duration);
+	}
+
+	// Placing and removing spawns at the playhead
+	if mouse.just_pressed(MouseButton::Left) {
+		let target = windows.single().ok()
+			.zip(camera_query.single().ok())
+			.and_then(|(window, camera)| cursor_to_world(window, camera));
+		if let Some(target) = target {
+			let offset = target - REACTOR_CENTRE;
+			if offset.length() > 0.0 {
+				let (index, playhead) = (editor.wave, editor.playhead);
+				let record = SpawnRecord {
+					time: playhead,
+					molecule: editor.molecule,
+					speed: offset.length(),
+					angle: (90.0 - offset.to_angle().to_degrees()).rem_euclid(360.0),
+					track_player: editor.track_player,
+				};
+				let wave = &mut editor.level.waves[index];
+				let position = wave.spawns.partition_point(|spawn| spawn.time <= playhead);
+				wave.spawns.insert(position, record);
+			}
+		}
+	}
+	if keys.just_pressed(KeyCode::Backspace) {
+		let (index, playhead) = (editor.wave, editor.playhead);
+		let wave = &mut editor.level.waves[index];
+		let position = wave.spawns.partition_point(|spawn| spawn.time <= playhead);
+		if position > 0 {
+			wave.spawns.remove(position - 1);
+		}
+	}
+
+	// Saving and loading
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	let ctrl = keys.any_pressed([KeyCode::ControlLeft, KeyCode::ControlRight]);
+	if ctrl && keys.just_pressed(KeyCode::KeyS) {
+		editor.status = match save_level(&editor.level, weapons) {
+			Ok(()) => format!("Saved {EDITOR_LEVEL_SAVE}"),
+			Err(error) => format!("Could not save: {error}"),
+		};
+	}
+	if ctrl && keys.just_pressed(KeyCode::KeyO) {
+		editor.status = match load_level(weapons) {
+			Ok(level) => {
+				editor.level = level;
+				editor.wave = 0;
+				editor.playhead = 0.0;
+				format!("Loaded {EDITOR_LEVEL_SAVE}")
+			}
+			Err(error) => format!("Could not load: {error}"),
+		};
+	}
+	if ctrl && keys.just_pressed(KeyCode::KeyE) {
+		editor.level = levels.get(&data.sample_level).expect("Could not find sample level").clone();
+		editor.wave = 0;
+		editor.playhead = 0.0;
+		editor.status = "Opened the sample level".to_string();
+	}
+
+	// Played as a level of its own, leaving the loaded one for normal runs
+	if keys.just_pressed(KeyCode::Enter) {
+		match editor.level.validate(weapons) {
+			Ok(()) => {
+				commands.insert_resource(PlayTest(levels.add(editor.level.clone())));
+				next_state.set(GameState::Playing);
+			}
+			Err(error) => editor.status = format!("Could not play: {error}"),
+		}
+	}
+
+	if keys.just_pressed(KeyCode::Escape) {
+		next_state.set(GameState::Menu);
+	}
+}
+
+fn save_level(level: &Level, weapons: &WeaponSet) -> Result<(), String> {
+	level.validate(weapons).map_err(|error| error.to_string())?;
+	save::store(EDITOR_LEVEL_SAVE, level).map_err(|error| error.to_string())
+}
+
+fn load_level(weapons: &WeaponSet) -> Result<Level, String> {
+	let level = save::load::<Level>(EDITOR_LEVEL_SAVE)
+		.map_err(|error| error.to_string())?
+		.ok_or_else(|| "nothing has been saved yet".to_string())?;
+	level.validate(weapons).map_err(|error| error.to_string())?;
+	Ok(level)
+}
+
+fn editor_playback(
+	mut editor: ResMut<EditorState>,
+	time: Res<Time>,
+) {
+	if editor.playing {
+		let duration = editor.level.waves[editor.wave].duration;
+		editor.playhead += time.delta_secs();
+		if editor.playhead > duration {
+			editor.playhead = 0.0;
+		}
+	}
+}
+
+/// Reflects a position back and forth between two walls, like a molecule bouncing between them
+fn bounce_between(pos: f32, min: f32, max: f32) -> f32 {
+	let width = max - min;
+	let travelled = (pos - min).rem_euclid(2.0 * width);
+	if travelled > width {min + 2.0 * width - travelled} else {min + travelled}
+}
+
+fn draw_editor(
+	mut gizmos: Gizmos,
+	editor: Res<EditorState>,
+	registry: Res<MoleculeRegistry>,
+	arena: Res<Arena>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	camera_query: Query<&Camera, With<MainCamera>>,
+) {
+	let wave = &editor.level.waves[editor.wave];
+	gizmos.circle_2d(REACTOR_CENTRE, editor.level.reactor_radius, Color::WHITE);
+
+	// Molecules already released by the playhead, bouncing off the same walls as `molecule_movement`
+	for record in wave.spawns.iter().filter(|record| record.time <= editor.playhead) {
+		let info = registry.get(record.molecule);
+		let radius = info.radius;
+		let direction = if record.track_player {(PLAYER_START - REACTOR_CENTRE).normalize()} else {record.direction()};
+		let spawn_point = REACTOR_CENTRE + editor.level.spawn_offset(direction, radius);
+		let travelled = spawn_point + direction * record.speed * (editor.playhead - record.time);
+		let bounds = arena.bounds(radius);
+		let pos = Vec2::new(
+			bounce_between(travelled.x, bounds.min.x, bounds.max.x),
+			bounce_between(travelled.y, bounds.min.y, bounds.max.y),
+		);
+		gizmos.circle_2d(pos, radius, info.colour);
+	}
+
+	if editor.track_player {
+		gizmos.circle_2d(PLAYER_START, 24.0, Color::WHITE);
+	}
+
+	// Timeline across the middle of the bottom wall, with a tick for each spawn
+	let floor = arena.floor();
+	let (start, end, y) = (floor.min.x, floor.max.x, floor.min.y - arena.walls.bottom / 2.0);
+	let to_x = |t: f32| start + (end - start) * t / wave.duration;
+	gizmos.line_2d(Vec2::new(start, y), Vec2::new(end, y), Color::linear_rgb(0.4, 0.64, 0.72));
+	for record in wave.spawns.iter() {
+		let x = to_x(record.time);
+		gizmos.line_2d(Vec2::new(x, y - 6.0), Vec2::new(x, y + 6.0), registry.get(record.molecule).colour);
+	}
+	let x = to_x(editor.playhead);
+	gizmos.line_2d(Vec2::new(x, y - 12.0), Vec2::new(x, y + 12.0), Color::WHITE);
+
+	// Aim of the next spawn
+	let (Ok(window), Ok(camera)) = (windows.single(), camera_query.single()) else {
+		return;
+	};
+	if let Some(target) = cursor_to_world(window, camera) {
+		let info = registry.get(editor.molecule);
+		gizmos.line_2d(REACTOR_CENTRE, target, info.colour);
+		gizmos.circle_2d(target, info.radius, info.colour);
+	}
+}
+
+fn resize_reactor(editor: Res<EditorState>, mut reactor_query: Query<&mut Sprite, With<EditorReactor>>) {
+	let size = Some(Vec2::splat(editor.level.reactor_radius * 2.0));
+	for mut sprite in reactor_query.iter_mut() {
+		if sprite.custom_size != size {
+			sprite.custom_size = size;
+		}
+	}
+}
+
+fn update_editor_text(
+	editor: Res<EditorState>,
+	registry: Res<MoleculeRegistry>,
+	mut text_query: Query<&mut Text, With<EditorText>>,
+) {
+	let mut text = text_query.single_mut().expect("Could not find editor text");
+	let wave = &editor.level.waves[editor.wave];
+	text.0 = format!(
+		"Wave {}/{}   Time {:.2}s / {:.2}s   Spawns {}\n\
+		Molecule {}   Track player {}   {}\n\n\
+		0-9 molecule   T track   Click place   Backspace remove\n\
+		Space play   Left/Right/Wheel scrub   Home rewind   -/= duration\n\
+		Up/Down wave   N new wave   Delete remove wave\n\
+		Ctrl+S save   Ctrl+O load   Ctrl+E sample   Enter play   Esc menu\n\n{}",
+		editor.wave + 1, editor.level.waves.len(), editor.playhead, wave.duration, wave.spawns.len(),
+		registry.get(editor.molecule).name, if editor.track_player {"on"} else {"off"}, if editor.playing {"playing"} else {"paused"},
+		editor.status,
+	);
+}
+
+fn end_play_test(mut commands: Commands) {
+	commands.remove_resource::<PlayTest>();
+}
+
+fn cleanup_editor(mut commands: Commands, editor: Query<Entity, With<Editor>>) {
+	for entity in editor.iter() {
+		commands.entity(entity).despawn();
+	}
+}
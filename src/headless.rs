@@ -0,0 +1,200 @@
+use std::time::Duration;
+
+use bevy::prelude::*;
+use bevy::state::app::StatesPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy_asset_loader::prelude::*;
+use thiserror::Error;
+
+use crate::highscores::HighScore;
+use crate::levels::LevelsPlugin;
+use crate::loading::{DataAssets, TextureAssets};
+use crate::meltdown::{MeltdownPlugin, ReactorHeat};
+use crate::molecules::MoleculesPlugin;
+use crate::obstacles::ObstaclesPlugin;
+use crate::player::{PlayerInfo, PlayerPlugin};
+use crate::reactions::ReactionsPlugin;
+use crate::registry::RegistryPlugin;
+use crate::simulation::{RunSeed, SimulationPlugin, SimulationSet, TICK_RATE};
+use crate::weapons::{Loadout, WeaponsPlugin};
+use crate::{GameState, PlayState};
+
+pub use crate::input::PlayerInput;
+pub use crate::replay::Replay;
+
+/// Where the input of a headless run comes from
+#[derive(Clone)]
+pub enum InputScript {
+	/// Plays a recorded run back with its own seed
+	Replay(Replay),
+	/// Asks `input` for the input of every tick
+	Scripted {
+		seed: u64,
+		input: fn(tick: u64) -> PlayerInput,
+	},
+}
+
+impl InputScript {
+	fn seed(&self) -> u64 {
+		match self {
+			InputScript::Replay(replay) => replay.seed,
+			InputScript::Scripted { seed, .. } => *seed,
+		}
+	}
+
+	/// Scripted runs start with the default weapon
+	fn weapon(&self) -> Option<String> {
+		match self {
+			InputScript::Replay(replay) => Some(replay.weapon.clone()),
+			InputScript::Scripted { .. } => None,
+		}
+	}
+
+	fn input(&self, tick: u64) -> PlayerInput {
+		match self {
+			InputScript::Replay(replay) => replay.ticks.get(tick as usize).copied().unwrap_or_default(),
+			InputScript::Scripted { input, .. } => input(tick),
+		}
+	}
+}
+
+/// Runs a single game without a window, renderer or audio
+/// Add it to an app with [`MinimalPlugins`], [`AssetPlugin`], [`StatesPlugin`] and [`TransformPlugin`], or use [`simulate`]
+/// Every update advances the simulation by exactly one tick, as fast as the machine allows
+#[derive(Clone)]
+pub struct HeadlessPlugin {
+	pub script: InputScript,
+	/// The run is cut short after this many ticks
+	pub max_ticks: u64,
+}
+
+impl Plugin for HeadlessPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_state::<GameState>()
+			.add_plugins((
+				SimulationPlugin,
+				RegistryPlugin,
+				ReactionsPlugin,
+				LevelsPlugin,
+				MoleculesPlugin,
+				ObstaclesPlugin,
+				MeltdownPlugin,
+				WeaponsPlugin,
+				PlayerPlugin,
+			))
+			.add_loading_state(
+				LoadingState::new(GameState::Loading)
+					.continue_to_state(GameState::Playing)
+					// Nothing else uses the menu without a window, so it stands in for a failed load
+					.on_failure_continue_to_state(GameState::Menu)
+					.load_collection::<DataAssets>(),
+			)
+			.insert_resource(TimeUpdateStrategy::ManualDuration(Duration::from_secs_f64(1.0 / TICK_RATE)))
+			.insert_resource(TextureAssets::default())
+			// Normally added by the sprite plugin, the player's animation needs somewhere to keep its layout
+			.init_asset::<TextureAtlasLayout>()
+			// Nor is there anything to load the weapons' images, which are asked for by path
+			.init_asset::<Image>()
+			.insert_resource(RunSeed { current: 0, next: Some(self.script.seed()) })
+			.insert_resource(Loadout { next: self.script.weapon(), ..default() })
+			.insert_resource(HeadlessRun {
+				script: self.script.clone(),
+				max_ticks: self.max_ticks,
+			})
+			.insert_resource(RunSummary { seed: self.script.seed(), ..default() })
+			.init_resource::<PlayerInput>()
+			// Kept in memory only, so simulated runs never touch the player's save
+			.init_resource::<HighScore>()
+			.add_systems(FixedUpdate, play_script.in_set(SimulationSet::Input))
+			.add_systems(FixedUpdate, summarise_tick.after(SimulationSet::Reactor).run_if(in_state(PlayState::Running)))
+			.add_systems(OnEnter(GameState::Retry), finish_run)
+			.add_systems(OnEnter(GameState::Menu), fail_to_load)
+			;
+	}
+}
+
+/// Outcome of a headless run
+#[derive(Resource, Clone, Default, PartialEq, Debug)]
+pub struct RunSummary {
+	pub seed: u64,
+	pub ticks: u64,
+	pub score: f32,
+	pub time_survived: f32,
+	/// Whether the player died, rather than the run hitting its tick limit
+	pub died: bool,
+	/// Whether the run ended in a meltdown rather than the player losing their lives
+	pub melted_down: bool,
+}
+
+#[derive(Debug, Error)]
+pub enum SimulationError {
+	#[error("could not load the game data")]
+	LoadFailed,
+}
+
+/// Plays a whole run headless and reports how it went
+pub fn simulate(script: InputScript, max_ticks: u64) -> Result<RunSummary, SimulationError> {
+	let mut app = App::new();
+	app.add_plugins((
+		MinimalPlugins,
+		AssetPlugin::default(),
+		StatesPlugin,
+		TransformPlugin,
+		HeadlessPlugin { script, max_ticks },
+	));
+	app.finish();
+	app.cleanup();
+	loop {
+		app.update();
+		if let Some(exit) = app.should_exit() {
+			return match exit {
+				AppExit::Success => Ok(app.world().resource::<RunSummary>().clone()),
+				AppExit::Error(_) => Err(SimulationError::LoadFailed),
+			};
+		}
+	}
+}
+
+#[derive(Resource)]
+struct HeadlessRun {
+	script: InputScript,
+	max_ticks: u64,
+}
+
+fn play_script(
+	run: Res<HeadlessRun>,
+	summary: Res<RunSummary>,
+	mut input: ResMut<PlayerInput>,
+) {
+	*input = run.script.input(summary.ticks);
+}
+
+fn summarise_tick(
+	mut summary: ResMut<RunSummary>,
+	mut app_exit: EventWriter<AppExit>,
+	run: Res<HeadlessRun>,
+	player_query: Query<&PlayerInfo>,
+) {
+	let Ok(p_info) = player_query.single() else {return};
+	summary.ticks += 1;
+	summary.score = p_info.score;
+	summary.time_survived = p_info.time_survived;
+	if summary.ticks >= run.max_ticks {
+		app_exit.write(AppExit::Success);
+	}
+}
+
+fn finish_run(
+	mut summary: ResMut<RunSummary>,
+	mut app_exit: EventWriter<AppExit>,
+	heat: Res<ReactorHeat>,
+) {
+	summary.died = true;
+	summary.melted_down = heat.melted_down();
+	app_exit.write(AppExit::Success);
+}
+
+fn fail_to_load(mut app_exit: EventWriter<AppExit>) {
+	error!("Could not load the game data");
+	app_exit.write(AppExit::error());
+}
@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::save;
+
+const HIGHSCORE_SAVE: &str = "highscore.ron";
+
+pub struct HighScorePlugin;
+
+/// This plugin loads the best score and time on startup and saves them whenever they are beaten
+impl Plugin for HighScorePlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<HighScore>()
+			.add_systems(Startup, load_highscore)
+			.add_systems(Update, save_highscore.run_if(
+				// Skips the first run, which sees the value that was just loaded
+				resource_changed::<HighScore>.and(not(resource_added::<HighScore>))
+			))
+			;
+	}
+}
+
+/// Best score and longest time survived over every run
+#[derive(Resource, Serialize, Deserialize, Default, Clone, Copy, PartialEq, Debug)]
+pub struct HighScore {
+	pub score: f32,
+	pub time: f32,
+}
+
+fn load_highscore(mut highscore: ResMut<HighScore>) {
+	match save::load(HIGHSCORE_SAVE) {
+		Ok(Some(saved)) => *highscore = saved,
+		Ok(None) => (),
+		Err(error) => warn!("Could not load high score, starting from zero: {error}"),
+	}
+}
+
+fn save_highscore(highscore: Res<HighScore>) {
+	if let Err(error) = save::store(HIGHSCORE_SAVE, &*highscore) {
+		warn!("Could not save high score: {error}");
+	}
+}
@@ -0,0 +1,199 @@
+use std::collections::HashSet;
+
+use bevy::{
+	prelude::*,
+	input::touch::Touch,
+	window::{CursorMoved, PrimaryWindow},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{update_actions, Action, ActionState};
+use crate::camera::{window_to_world, MainCamera};
+use crate::replay::ReplayPlayback;
+use crate::settings::Settings;
+use crate::simulation::SimulationSet;
+use crate::touch::{on_pause_button, on_swing_button};
+use crate::{GameState, PlayState};
+
+/// Stick deflection ignored as drift
+const STICK_DEADZONE: f32 = 0.2;
+
+pub struct PlayerInputPlugin;
+
+/// This plugin samples the bound actions, the cursor, the sticks and touches every frame and hands the result to the simulation once per tick
+/// While a replay is playing the recorded input is used instead
+impl Plugin for PlayerInputPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<InputSampler>()
+			.init_resource::<PlayerInput>()
+			.add_systems(PreUpdate, sample_input.after(update_actions))
+			.add_systems(FixedUpdate, consume_input
+				.run_if(not(resource_exists::<ReplayPlayback>))
+				.in_set(SimulationSet::Input))
+			.add_systems(OnEnter(GameState::Playing), ignore_held_button)
+			.add_systems(OnExit(PlayState::Paused), ignore_held_button)
+			;
+	}
+}
+
+/// Which device steers the player
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum ControlScheme {
+	/// Whichever device was used last
+	#[default]
+	Auto,
+	/// Steer toward the cursor
+	Mouse,
+	/// Move and aim with the bound keys
+	Keyboard,
+	/// Left stick to move and right stick to aim, as well as the bound buttons
+	Gamepad,
+	/// Drag to steer toward the finger, hold a second finger or the swing button to wind up
+	Touch,
+}
+
+/// Input for a single simulation tick, the only input gameplay systems read
+/// Replays and scripted runs supply this directly in place of the player's devices
+#[derive(Resource, Default, Clone, Copy, PartialEq, Debug)]
+pub struct PlayerInput {
+	/// World position the player steers and aims towards, only set when playing with the mouse or a touch screen
+	pub target: Option<Vec2>,
+	/// Direction to move in, no longer than 1
+	pub movement: Vec2,
+	/// Direction to face, if different from the direction of movement
+	pub aim: Option<Vec2>,
+	/// Whether the swing button is held
+	pub swing: bool,
+}
+
+/// Input gathered between ticks
+#[derive(Resource, Default)]
+pub struct InputSampler {
+	/// Device used most recently, followed by [`ControlScheme::Auto`]
+	last_used: ControlScheme,
+	/// Scheme read from on the last frame
+	scheme: ControlScheme,
+	target: Option<Vec2>,
+	movement: Vec2,
+	aim: Option<Vec2>,
+	held: bool,
+	/// Set when the button goes down, so a click that starts and ends between two ticks still registers
+	pressed_since_tick: bool,
+	/// Set when leaving the pause menu, so the click that closed it doesn't also swing
+	wait_for_release: bool,
+	/// Touches still held from a menu, which neither steer nor swing until lifted
+	ignored_touches: HashSet<u64>,
+}
+
+impl InputSampler {
+	pub fn scheme(&self) -> ControlScheme {
+		self.scheme
+	}
+}
+
+
+fn stick(value: Vec2) -> Option<Vec2> {
+	(value.length() > STICK_DEADZONE).then(|| value.clamp_length_max(1.0))
+}
+
+fn sticks_in_use(gamepad: &Gamepad) -> bool {
+	stick(gamepad.left_stick()).is_some() || stick(gamepad.right_stick()).is_some()
+}
+
+fn sample_input(
+	mut sampler: ResMut<InputSampler>,
+	mut cursor_events: EventReader<CursorMoved>,
+	windows: Query<&Window, With<PrimaryWindow>>,
+	camera_query: Query<&Camera, With<MainCamera>>,
+	actions: Res<ActionState>,
+	gamepads: Query<&Gamepad>,
+	touches: Res<Touches>,
+	settings: Res<Settings>,
+	ui_scale: Res<UiScale>,
+) {
+	let Ok(window) = windows.single() else {return};
+	let Ok(camera) = camera_query.single() else {return};
+	let Some(viewport) = camera.logical_viewport_rect() else {return};
+	// The gamepad being touched, or failing that the first one connected
+	let gamepad = gamepads.iter().find(|gamepad| sticks_in_use(gamepad)).or_else(|| gamepads.iter().next());
+
+	// Some browsers move the cursor along with a finger, which shouldn't count as using the mouse
+	if cursor_events.read().count() > 0 && touches.iter().next().is_none() {
+		sampler.last_used = ControlScheme::Mouse;
+	}
+	if gamepad.is_some_and(sticks_in_use) {
+		sampler.last_used = ControlScheme::Gamepad;
+	}
+	if let Some(scheme) = actions.just_used() {
+		sampler.last_used = scheme;
+	}
+	if touches.any_just_pressed() {
+		sampler.last_used = ControlScheme::Touch;
+	}
+	let scheme = match settings.controls {
+		ControlScheme::Auto => sampler.last_used,
+		scheme => scheme,
+	};
+	sampler.scheme = scheme;
+
+	let movement_actions = actions.direction(Action::MoveUp, Action::MoveDown, Action::MoveLeft, Action::MoveRight);
+	let aim_actions = Some(actions.direction(Action::AimUp, Action::AimDown, Action::AimLeft, Action::AimRight))
+		.filter(|aim| *aim != Vec2::ZERO);
+	let (mut target, mut movement, mut aim) = (None, Vec2::ZERO, None);
+	let (mut pressed, mut just_pressed) = (actions.pressed(Action::Swing), actions.just_pressed(Action::Swing));
+	sampler.ignored_touches.retain(|&id| touches.get_pressed(id).is_some());
+	match scheme {
+		ControlScheme::Auto | ControlScheme::Mouse => {
+			target = window.cursor_position().and_then(|position| window_to_world(camera, position));
+		}
+		ControlScheme::Keyboard => {
+			movement = movement_actions;
+			aim = aim_actions;
+		}
+		ControlScheme::Gamepad => {
+			movement = gamepad.and_then(|gamepad| stick(gamepad.left_stick())).unwrap_or(movement_actions);
+			aim = gamepad.and_then(|gamepad| stick(gamepad.right_stick())).or(aim_actions);
+		}
+		ControlScheme::Touch => {
+			// Taps on the pause button are for the UI, they mustn't steer or swing for the tick or two before it pauses
+			let (swing_touches, mut move_touches): (Vec<&Touch>, Vec<&Touch>) = touches.iter()
+				.filter(|touch| !sampler.ignored_touches.contains(&touch.id()))
+				.filter(|touch| !on_pause_button(viewport, ui_scale.0, touch.start_position()))
+				.partition(|touch| on_swing_button(viewport, ui_scale.0, touch.position()));
+			// The earliest finger steers, any finger after it winds up the swing
+			move_touches.sort_by_key(|touch| touch.id());
+			target = move_touches.first().and_then(|touch| window_to_world(camera, touch.position()));
+			pressed |= !swing_touches.is_empty() || move_touches.len() > 1;
+			just_pressed |= swing_touches.iter().chain(move_touches.iter().skip(1))
+				.any(|touch| touches.just_pressed(touch.id()));
+		}
+	}
+
+	sampler.target = target;
+	sampler.movement = movement;
+	sampler.aim = aim;
+	if !pressed {
+		sampler.wait_for_release = false;
+	}
+	sampler.held = pressed && !sampler.wait_for_release;
+	if just_pressed && !sampler.wait_for_release {
+		sampler.pressed_since_tick = true;
+	}
+}
+
+fn ignore_held_button(mut sampler: ResMut<InputSampler>, touches: Res<Touches>) {
+	sampler.ignored_touches = touches.iter().map(Touch::id).collect();
+	sampler.pressed_since_tick = false;
+	sampler.wait_for_release = true;
+}
+
+pub fn consume_input(
+	mut sampler: ResMut<InputSampler>,
+	mut input: ResMut<PlayerInput>,
+) {
+	input.target = sampler.target;
+	input.movement = sampler.movement;
+	input.aim = sampler.aim;
+	input.swing = sampler.held || sampler.pressed_since_tick;
+	sampler.pressed_since_tick = false;
+}
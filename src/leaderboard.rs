@@ -0,0 +1,463 @@
+use std::cmp::Ordering;
+
+use bevy::input::keyboard::{Key, KeyboardInput};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::{Action, ActionState};
+use crate::input::ControlScheme;
+use crate::save;
+use crate::ui::{button, button_label, ButtonColors, BORDER_COLOUR, TEXT_COLOUR};
+use crate::GameState;
+
+const LEADERBOARD_SAVE: &str = "leaderboard.ron";
+/// Entries shown, an entry is kept while it places this high by score or by time
+const LEADERBOARD_SIZE: usize = 10;
+const MAX_NAME_LENGTH: usize = 12;
+const DEFAULT_NAME: &str = "RANCHER";
+
+pub struct LeaderboardPlugin;
+
+/// This plugin keeps the local leaderboard, asks for a name when a run places on it,
+/// and fills in every panel spawned with [`spawn_leaderboard_panel`]
+impl Plugin for LeaderboardPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<Leaderboard>()
+			.init_resource::<LeaderboardSort>()
+			.add_systems(Startup, load_leaderboard)
+			.add_systems(OnEnter(GameState::Retry), prompt_for_name)
+			.add_systems(Update, (type_name, update_name_prompt).chain().run_if(resource_exists::<PendingEntry>))
+			.add_systems(Update, (click_sort_button, update_leaderboard_panels).chain())
+			.add_systems(OnExit(GameState::Retry), submit_pending_entry)
+			;
+	}
+}
+
+/// The result of the run that just ended, left out when a replay ends
+#[derive(Resource)]
+pub struct FinishedRun {
+	pub score: f32,
+	pub time: f32,
+	pub weapon: String,
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Debug)]
+pub struct LeaderboardEntry {
+	pub name: String,
+	pub score: f32,
+	pub time: f32,
+	/// Seconds since the Unix epoch
+	pub date: u64,
+	/// The weapon the run started with, which is all that sets one run's rules apart from another's
+	/// Empty for entries saved before it was recorded
+	#[serde(default)]
+	pub weapon: String,
+}
+
+#[derive(Resource, Serialize, Deserialize, Default)]
+pub struct Leaderboard {
+	entries: Vec<LeaderboardEntry>,
+	/// Offered as the name for the next entry
+	last_name: String,
+}
+
+#[derive(Resource, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum LeaderboardSort {
+	#[default]
+	Score,
+	Time,
+}
+
+impl LeaderboardSort {
+	/// Best first, earlier entries win ties
+	fn compare(self, a: &LeaderboardEntry, b: &LeaderboardEntry) -> Ordering {
+		let (a_key, b_key) = match self {
+			LeaderboardSort::Score => ((a.score, a.time), (b.score, b.time)),
+			LeaderboardSort::Time => ((a.time, a.score), (b.time, b.score)),
+		};
+		b_key.partial_cmp(&a_key).unwrap_or(Ordering::Equal).then(a.date.cmp(&b.date))
+	}
+}
+
+impl Leaderboard {
+	/// The top entries in `sort` order
+	pub fn top(&self, sort: LeaderboardSort) -> Vec<&LeaderboardEntry> {
+		let mut entries: Vec<_> = self.entries.iter().collect();
+		entries.sort_by(|a, b| sort.compare(a, b));
+		entries.truncate(LEADERBOARD_SIZE);
+		entries
+	}
+
+	fn qualifies(&self, score: f32, time: f32) -> bool {
+		self.entries.iter().filter(|entry| entry.score >= score).count() < LEADERBOARD_SIZE
+			|| self.entries.iter().filter(|entry| entry.time >= time).count() < LEADERBOARD_SIZE
+	}
+
+	fn insert(&mut self, entry: LeaderboardEntry) {
+		self.last_name = entry.name.clone();
+		self.entries.push(entry);
+		// Only entries placing on either table are worth keeping
+		let kept: Vec<_> = self.top(LeaderboardSort::Score).into_iter()
+			.chain(self.top(LeaderboardSort::Time))
+			.cloned()
+			.collect();
+		self.entries.retain(|entry| kept.contains(entry));
+	}
+}
+
+/// A run waiting for its name to be typed in before it joins the leaderboard
+#[derive(Resource)]
+struct PendingEntry(LeaderboardEntry);
+
+#[derive(Component)]
+struct NamePrompt;
+
+#[derive(Component)]
+struct NamePromptText;
+
+#[derive(Component)]
+pub struct LeaderboardPanel;
+
+#[derive(Component)]
+struct SortButton;
+
+#[derive(Component)]
+struct SortLabel;
+
+#[derive(Component, Clone, Copy)]
+enum LeaderboardColumn {
+	Rank,
+	Name,
+	Score,
+	Time,
+	Weapon,
+	Date,
+}
+
+impl LeaderboardColumn {
+	const ALL: [LeaderboardColumn; 6] = [
+		LeaderboardColumn::Rank,
+		LeaderboardColumn::Name,
+		LeaderboardColumn::Score,
+		LeaderboardColumn::Time,
+		LeaderboardColumn::Weapon,
+		LeaderboardColumn::Date,
+	];
+
+	fn width(self) -> f32 {
+		match self {
+			LeaderboardColumn::Rank => 30.0,
+			LeaderboardColumn::Name => 130.0,
+			LeaderboardColumn::Score => 70.0,
+			LeaderboardColumn::Time => 80.0,
+			LeaderboardColumn::Weapon => 90.0,
+			LeaderboardColumn::Date => 96.0,
+		}
+	}
+
+	fn header(self) -> &'static str {
+		match self {
+			LeaderboardColumn::Rank => "#",
+			LeaderboardColumn::Name => "NAME",
+			LeaderboardColumn::Score => "SCORE",
+			LeaderboardColumn::Time => "TIME",
+			LeaderboardColumn::Weapon => "WEAPON",
+			LeaderboardColumn::Date => "DATE",
+		}
+	}
+
+	fn cell(self, rank: usize, entry: &LeaderboardEntry) -> String {
+		match self {
+			LeaderboardColumn::Rank => format!("{}", rank + 1),
+			LeaderboardColumn::Name => entry.name.clone(),
+			LeaderboardColumn::Score => format!("{}", entry.score),
+			LeaderboardColumn::Time => format_time(entry.time),
+			LeaderboardColumn::Weapon => if entry.weapon.is_empty() {"-".to_string()} else {entry.weapon.to_uppercase()},
+			LeaderboardColumn::Date => format_date(entry.date),
+		}
+	}
+}
+
+fn format_time(time: f32) -> String {
+	if time < 60.0 {format!("{:.2}s", time)}
+	else {format!("{:.0}m {:.0}s", (time/60.0).floor(), time.floor() % 60.0)}
+}
+
+/// Formats as YYYY-MM-DD, using the days-to-civil algorithm from <https://howardhinnant.github.io/date_algorithms.html>
+fn format_date(date: u64) -> String {
+	let days = (date / 86400) as i64 + 719468;
+	let era = days.div_euclid(146097);
+	let day_of_era = days.rem_euclid(146097);
+	let year_of_era = (day_of_era - day_of_era/1460 + day_of_era/36524 - day_of_era/146096) / 365;
+	let day_of_year = day_of_era - (365*year_of_era + year_of_era/4 - year_of_era/100);
+	let shifted_month = (5*day_of_year + 2) / 153;
+	let day = day_of_year - (153*shifted_month + 2)/5 + 1;
+	let month = if shifted_month < 10 {shifted_month + 3} else {shifted_month - 9};
+	let year = year_of_era + era * 400 + (month <= 2) as i64;
+	format!("{year:04}-{month:02}-{day:02}")
+}
+
+fn now() -> u64 {
+	#[cfg(not(target_arch = "wasm32"))]
+	{
+		std::time::SystemTime::now()
+			.duration_since(std::time::UNIX_EPOCH)
+			.map(|duration| duration.as_secs())
+			.unwrap_or(0)
+	}
+	#[cfg(target_arch = "wasm32")]
+	{
+		(js_sys::Date::now() / 1000.0) as u64
+	}
+}
+
+/// Spawns a leaderboard with `node` as its layout, the plugin keeps its contents up to date
+pub fn spawn_leaderboard_panel(commands: &mut Commands, node: Node, extra: impl Bundle) {
+	commands
+	.spawn((
+		Node {
+			flex_direction: FlexDirection::Column,
+			align_items: AlignItems::Center,
+			padding: UiRect::all(Val::Px(10.0)),
+			border: UiRect::all(Val::Px(2.0)),
+			row_gap: Val::Px(6.0),
+			..node
+		},
+		BorderColor(BORDER_COLOUR),
+		BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.8)),
+		LeaderboardPanel,
+		extra,
+	))
+	.with_children(|children| {
+		children
+			.spawn(Node {
+				width: Val::Percent(100.0),
+				justify_content: JustifyContent::SpaceBetween,
+				align_items: AlignItems::Center,
+				..default()
+			})
+			.with_children(|header| {
+				header.spawn((
+					Text::new("LEADERBOARD"),
+					TextFont {
+						font_size: 25.0,
+						..default()
+					},
+					TextColor(TEXT_COLOUR),
+				));
+				header
+					.spawn(button(Node {width: Val::Px(130.0), height: Val::Px(40.0), ..default()}, SortButton))
+					.with_child((button_label("", 16.0), SortLabel));
+			});
+		children
+			.spawn(Node::default())
+			.with_children(|columns| {
+				for column in LeaderboardColumn::ALL {
+					columns.spawn((
+						Text::default(),
+						TextFont {
+							font_size: 16.0,
+							..default()
+						},
+						TextColor(TEXT_COLOUR),
+						Node {
+							width: Val::Px(column.width()),
+							..default()
+						},
+						column,
+					));
+				}
+			});
+	});
+}
+
+fn load_leaderboard(mut leaderboard: ResMut<Leaderboard>) {
+	match save::load(LEADERBOARD_SAVE) {
+		Ok(Some(saved)) => *leaderboard = saved,
+		Ok(None) => (),
+		Err(error) => warn!("Could not load leaderboard, starting empty: {error}"),
+	}
+}
+
+fn prompt_for_name(
+	mut commands: Commands,
+	leaderboard: Res<Leaderboard>,
+	finished_run: Option<Res<FinishedRun>>,
+) {
+	let Some(run) = finished_run else {return};
+	commands.remove_resource::<FinishedRun>();
+	if !leaderboard.qualifies(run.score, run.time) {return};
+
+	let name = if leaderboard.last_name.is_empty() {DEFAULT_NAME.to_string()} else {leaderboard.last_name.clone()};
+	commands.insert_resource(PendingEntry(LeaderboardEntry {
+		name,
+		score: run.score,
+		time: run.time,
+		date: now(),
+		weapon: run.weapon.clone(),
+	}));
+	commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Percent(70.0),
+			width: Val::Px(520.0),
+			margin: UiRect::left(Val::Px(-260.0)),
+			padding: UiRect::all(Val::Px(8.0)),
+			border: UiRect::all(Val::Px(2.0)),
+			justify_content: JustifyContent::Center,
+			..default()
+		},
+		BorderColor(BORDER_COLOUR),
+		BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.8)),
+		NamePrompt,
+	))
+	.with_child((
+		Text::default(),
+		TextFont {
+			font_size: 20.0,
+			..default()
+		},
+		TextColor(TEXT_COLOUR),
+		NamePromptText,
+	));
+}
+
+fn type_name(
+	mut commands: Commands,
+	mut keyboard_events: EventReader<KeyboardInput>,
+	mut pending: ResMut<PendingEntry>,
+	mut leaderboard: ResMut<Leaderboard>,
+	actions: Res<ActionState>,
+	prompt_query: Query<Entity, With<NamePrompt>>,
+) {
+	// Without a keyboard the offered name can still be saved or the entry skipped,
+	// keyboards only use their own keys since the bound ones may be letters being typed
+	if actions.just_used() != Some(ControlScheme::Keyboard) {
+		if actions.just_pressed(Action::Swing) {
+			submit(&mut commands, &mut leaderboard, &pending, &prompt_query);
+			return;
+		}
+		if actions.just_pressed(Action::Pause) {
+			close_prompt(&mut commands, &prompt_query);
+			return;
+		}
+	}
+
+	for event in keyboard_events.read() {
+		if !event.state.is_pressed() {continue};
+		let name = &mut pending.0.name;
+		match &event.logical_key {
+			Key::Character(characters) => {
+				for character in characters.chars() {
+					if (character.is_ascii_alphanumeric() || character == ' ' || character == '-') && name.len() < MAX_NAME_LENGTH {
+						name.push(character.to_ascii_uppercase());
+					}
+				}
+			}
+			Key::Space if name.len() < MAX_NAME_LENGTH => name.push(' '),
+			Key::Backspace => {
+				name.pop();
+			}
+			Key::Enter => {
+				submit(&mut commands, &mut leaderboard, &pending, &prompt_query);
+				return;
+			}
+			Key::Escape => {
+				close_prompt(&mut commands, &prompt_query);
+				return;
+			}
+			_ => (),
+		}
+	}
+}
+
+fn update_name_prompt(
+	pending: Res<PendingEntry>,
+	mut text_query: Query<&mut Text, With<NamePromptText>>,
+) {
+	if !pending.is_changed() {return};
+	for mut text in text_query.iter_mut() {
+		text.0 = format!("NEW RECORD! NAME: {}_\nENTER OR SWING TO SAVE, ESC OR PAUSE TO SKIP", pending.0.name);
+	}
+}
+
+/// Saves whatever name was typed if the retry screen is left without confirming it
+fn submit_pending_entry(
+	mut commands: Commands,
+	mut leaderboard: ResMut<Leaderboard>,
+	pending: Option<Res<PendingEntry>>,
+	prompt_query: Query<Entity, With<NamePrompt>>,
+) {
+	if let Some(pending) = pending {
+		submit(&mut commands, &mut leaderboard, &pending, &prompt_query);
+	}
+}
+
+fn submit(
+	commands: &mut Commands,
+	leaderboard: &mut Leaderboard,
+	pending: &PendingEntry,
+	prompt_query: &Query<Entity, With<NamePrompt>>,
+) {
+	let mut entry = pending.0.clone();
+	entry.name = entry.name.trim().to_string();
+	if entry.name.is_empty() {
+		entry.name = DEFAULT_NAME.to_string();
+	}
+	leaderboard.insert(entry);
+	if let Err(error) = save::store(LEADERBOARD_SAVE, leaderboard) {
+		warn!("Could not save leaderboard: {error}");
+	}
+	close_prompt(commands, prompt_query);
+}
+
+/// Drops the pending entry, leaving the run off the leaderboard unless it was submitted
+fn close_prompt(commands: &mut Commands, prompt_query: &Query<Entity, With<NamePrompt>>) {
+	commands.remove_resource::<PendingEntry>();
+	for entity in prompt_query.iter() {
+		commands.entity(entity).despawn();
+	}
+}
+
+fn click_sort_button(
+	mut sort: ResMut<LeaderboardSort>,
+	mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &ButtonColors), (Changed<Interaction>, With<SortButton>)>,
+) {
+	for (interaction, mut color, button_colors) in interaction_query.iter_mut() {
+		match *interaction {
+			Interaction::Pressed => {
+				*sort = match *sort {
+					LeaderboardSort::Score => LeaderboardSort::Time,
+					LeaderboardSort::Time => LeaderboardSort::Score,
+				};
+			}
+			Interaction::Hovered => *color = button_colors.hovered.into(),
+			Interaction::None => *color = button_colors.normal.into(),
+		}
+	}
+}
+
+fn update_leaderboard_panels(
+	leaderboard: Res<Leaderboard>,
+	sort: Res<LeaderboardSort>,
+	new_panels: Query<(), Added<LeaderboardPanel>>,
+	mut column_query: Query<(&mut Text, &LeaderboardColumn), Without<SortLabel>>,
+	mut label_query: Query<&mut Text, With<SortLabel>>,
+) {
+	if !leaderboard.is_changed() && !sort.is_changed() && new_panels.is_empty() {return};
+
+	let entries = leaderboard.top(*sort);
+	for (mut text, &column) in column_query.iter_mut() {
+		let mut lines = vec![column.header().to_string()];
+		lines.extend(entries.iter().enumerate().map(|(rank, entry)| column.cell(rank, entry)));
+		text.0 = lines.join("\n");
+	}
+	for mut text in label_query.iter_mut() {
+		text.0 = match *sort {
+			LeaderboardSort::Score => "BY SCORE".to_string(),
+			LeaderboardSort::Time => "BY TIME".to_string(),
+		};
+	}
+}
@@ -0,0 +1,191 @@
+use bevy::asset::{io::Reader, AssetLoader, LoadContext, LoadDirectError};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::obstacles::{ObstacleRecord, Shape};
+use crate::player::WeaponMode;
+use crate::weapons::{PickupRecord, WeaponSet, WEAPONS_PATH};
+use crate::registry::MoleculeKind;
+
+pub struct LevelsPlugin;
+
+/// Registers the level asset so it can be loaded by [`crate::loading::LoadingPlugin`]
+impl Plugin for LevelsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_asset::<Level>()
+			.init_asset_loader::<LevelLoader>();
+	}
+}
+
+/// A run made of waves that are played in order, looping back to the first
+#[derive(Asset, TypePath, Serialize, Deserialize, Clone, Debug)]
+pub struct Level {
+	pub waves: Vec<Wave>,
+	/// In place for the whole run
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub obstacles: Vec<ObstacleRecord>,
+	/// Size of the reactor in the middle of the room, molecules leave from its rim
+	#[serde(default = "default_reactor_radius")]
+	pub reactor_radius: f32,
+	#[serde(default)]
+	pub weapon_mode: WeaponMode,
+}
+
+fn default_reactor_radius() -> f32 {
+	64.0
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Wave {
+	pub duration: f32,
+	pub spawns: Vec<SpawnRecord>,
+	/// In place only while this wave is playing
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub obstacles: Vec<ObstacleRecord>,
+	/// Weapons lying around while this wave is playing
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub pickups: Vec<PickupRecord>,
+}
+
+/// A single molecule leaving the reactor `time` seconds into its wave
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SpawnRecord {
+	pub time: f32,
+	pub molecule: MoleculeKind,
+	pub speed: f32,
+	/// Degrees clockwise from straight up, ignored when tracking the player
+	pub angle: f32,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	pub track_player: bool,
+}
+
+impl SpawnRecord {
+	/// Direction the molecule leaves the reactor in when it isn't tracking the player
+	pub fn direction(&self) -> Vec2 {
+		Vec2::from_angle((-self.angle).to_radians()).rotate(Vec2::Y)
+	}
+}
+
+#[derive(Debug, Error)]
+pub enum LevelError {
+	#[error("could not read level: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("could not parse level: {0}")]
+	Parse(#[from] ron::error::SpannedError),
+	#[error("could not load the weapons for the level's pickups: {0}")]
+	Weapons(#[from] Box<LoadDirectError>),
+	#[error("level has no waves")]
+	NoWaves,
+	#[error("wave {wave} has a duration of {duration}, it must be greater than zero")]
+	InvalidDuration { wave: usize, duration: f32 },
+	#[error("wave {wave} spawn {spawn}: {reason}")]
+	InvalidSpawn { wave: usize, spawn: usize, reason: String },
+	#[error("reactor radius {0} must be greater than zero")]
+	InvalidReactorRadius(f32),
+	#[error("{} obstacle {obstacle}: {reason}", wave.map_or("level".to_string(), |wave| format!("wave {wave}")))]
+	InvalidObstacle { wave: Option<usize>, obstacle: usize, reason: String },
+	#[error("wave {wave} pickup {pickup}: {reason}")]
+	InvalidPickup { wave: usize, pickup: usize, reason: String },
+}
+
+impl Level {
+	/// Where a molecule of `radius` heading in `direction` leaves the reactor, relative to its centre
+	pub fn spawn_offset(&self, direction: Vec2, radius: f32) -> Vec2 {
+		direction * (self.reactor_radius + radius)
+	}
+
+	/// Checks the level can be played, with its pickups naming weapons in `weapons`
+	pub fn validate(&self, weapons: &WeaponSet) -> Result<(), LevelError> {
+		if self.waves.is_empty() {
+			return Err(LevelError::NoWaves);
+		}
+		if !(self.reactor_radius.is_finite() && self.reactor_radius > 0.0) {
+			return Err(LevelError::InvalidReactorRadius(self.reactor_radius));
+		}
+		validate_obstacles(None, &self.obstacles)?;
+		for (wave, w) in self.waves.iter().enumerate() {
+			if !(w.duration.is_finite() && w.duration > 0.0) {
+				return Err(LevelError::InvalidDuration { wave, duration: w.duration });
+			}
+			let mut previous_time = 0.0;
+			for (spawn, record) in w.spawns.iter().enumerate() {
+				let invalid = |reason: String| LevelError::InvalidSpawn { wave, spawn, reason };
+				if !record.time.is_finite() || record.time < 0.0 || record.time > w.duration {
+					return Err(invalid(format!("time {} is outside the wave's 0 to {} seconds", record.time, w.duration)));
+				}
+				if record.time < previous_time {
+					return Err(invalid(format!("time {} comes before the previous spawn at {}", record.time, previous_time)));
+				}
+				if !record.speed.is_finite() || record.speed < 0.0 {
+					return Err(invalid(format!("speed {} must be a positive number", record.speed)));
+				}
+				if !record.angle.is_finite() {
+					return Err(invalid(format!("angle {} must be a number", record.angle)));
+				}
+				previous_time = record.time;
+			}
+			validate_obstacles(Some(wave), &w.obstacles)?;
+			for (pickup, record) in w.pickups.iter().enumerate() {
+				let invalid = |reason: String| LevelError::InvalidPickup { wave, pickup, reason };
+				if !record.position.is_finite() {
+					return Err(invalid(format!("position {} must be a number", record.position)));
+				}
+				if weapons.get(&record.weapon).is_none() {
+					return Err(invalid(format!("unknown weapon `{}`", record.weapon)));
+				}
+			}
+		}
+		Ok(())
+	}
+}
+
+fn validate_obstacles(wave: Option<usize>, obstacles: &[ObstacleRecord]) -> Result<(), LevelError> {
+	for (obstacle, record) in obstacles.iter().enumerate() {
+		let invalid = |reason: String| LevelError::InvalidObstacle { wave, obstacle, reason };
+		if !record.position.is_finite() {
+			return Err(invalid(format!("position {} must be a number", record.position)));
+		}
+		match record.shape {
+			Shape::Circle { radius } => if !(radius.is_finite() && radius > 0.0) {
+				return Err(invalid(format!("radius {radius} must be greater than zero")));
+			},
+			Shape::Box { size, angle } => {
+				if !(size.is_finite() && size.min_element() > 0.0) {
+					return Err(invalid(format!("size {size} must be greater than zero")));
+				}
+				if !angle.is_finite() {
+					return Err(invalid(format!("angle {angle} must be a number")));
+				}
+			}
+		}
+	}
+	Ok(())
+}
+
+#[derive(Default)]
+struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+	type Asset = Level;
+	type Settings = ();
+	type Error = LevelError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let level = ron::de::from_bytes::<Level>(&bytes)?;
+		let weapons = load_context.loader().immediate().load::<WeaponSet>(WEAPONS_PATH).await.map_err(Box::new)?;
+		level.validate(weapons.get())?;
+		Ok(level)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["level.ron"]
+	}
+}
@@ -1,20 +1,61 @@
 #![allow(clippy::type_complexity)]
 
+mod actions;
+mod arena;
 mod audio;
+mod camera;
+mod editor;
+pub mod headless;
+mod highscores;
+mod input;
+mod leaderboard;
+mod levels;
 mod loading;
+mod meltdown;
 mod menu;
+mod pause;
 mod retry;
 mod molecules;
+mod obstacles;
 mod player;
+// Kept as copied from the Bevy example, its banner and the `ShaderType` derive's helpers trip newer lints
+#[allow(dead_code, clippy::empty_line_after_doc_comments)]
 mod postprocess;
+mod reactions;
+mod registry;
+mod replay;
+mod save;
+mod settings;
+mod simulation;
+mod spatial_hash;
+mod touch;
+mod ui;
+mod weapons;
 
+use crate::actions::ActionsPlugin;
 use crate::audio::InternalAudioPlugin;
+use crate::camera::CameraPlugin;
+use crate::editor::EditorPlugin;
+use crate::highscores::HighScorePlugin;
+use crate::input::PlayerInputPlugin;
+use crate::leaderboard::LeaderboardPlugin;
+use crate::levels::LevelsPlugin;
 use crate::loading::LoadingPlugin;
+use crate::meltdown::MeltdownPlugin;
 use crate::menu::MenuPlugin;
+use crate::pause::PausePlugin;
 use crate::retry::RetryPlugin;
 use crate::molecules::MoleculesPlugin;
+use crate::obstacles::ObstaclesPlugin;
 use crate::player::PlayerPlugin;
 use crate::postprocess::PostProcessPlugin;
+use crate::reactions::ReactionsPlugin;
+use crate::registry::RegistryPlugin;
+use crate::replay::ReplayPlugin;
+use crate::settings::SettingsPlugin;
+use crate::simulation::SimulationPlugin;
+use crate::touch::TouchControlsPlugin;
+use crate::weapons::{WeaponPickerPlugin, WeaponsPlugin};
 
 use bevy::app::App;
 use bevy::prelude::*;
@@ -26,6 +67,20 @@ enum GameState {
     Playing,
     Menu,
 	Retry,
+	Editor,
+	Settings,
+	Bindings,
+	/// Passed through for a frame to start a fresh run from inside one
+	Restart,
+}
+
+/// Only exists during [`GameState::Playing`], gameplay only advances while `Running`
+#[derive(SubStates, Default, Clone, Eq, PartialEq, Debug, Hash)]
+#[source(GameState = GameState::Playing)]
+enum PlayState {
+	#[default]
+	Running,
+	Paused,
 }
 
 pub struct GamePlugin;
@@ -34,12 +89,31 @@ impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
         app.init_state::<GameState>().add_plugins((
             PostProcessPlugin,
+            SimulationPlugin,
+            PlayerInputPlugin,
+            ReplayPlugin,
+            RegistryPlugin,
+            ReactionsPlugin,
+            LevelsPlugin,
             LoadingPlugin,
             MenuPlugin,
 			RetryPlugin,
             InternalAudioPlugin,
 			MoleculesPlugin,
             PlayerPlugin,
+			EditorPlugin,
+        )).add_plugins((
+            HighScorePlugin,
+            LeaderboardPlugin,
+            PausePlugin,
+            CameraPlugin,
+            SettingsPlugin,
+            ActionsPlugin,
+            TouchControlsPlugin,
+            ObstaclesPlugin,
+            MeltdownPlugin,
+            WeaponsPlugin,
+            WeaponPickerPlugin,
         ));
     }
 }
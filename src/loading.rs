@@ -1,3 +1,6 @@
+use crate::levels::Level;
+use crate::reactions::ReactionTable;
+use crate::weapons::WeaponSet;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -14,7 +17,8 @@ impl Plugin for LoadingPlugin {
             LoadingState::new(GameState::Loading)
                 .continue_to_state(GameState::Menu)
                 .load_collection::<AudioAssets>()
-                .load_collection::<TextureAssets>(),
+                .load_collection::<TextureAssets>()
+                .load_collection::<DataAssets>(),
         );
     }
 }
@@ -38,7 +42,8 @@ pub struct AudioAssets {
 	pub radiation_hit: Handle<AudioSource>,
 }
 
-#[derive(AssetCollection, Resource)]
+/// Headless runs insert the default, where every handle points at nothing
+#[derive(AssetCollection, Resource, Default)]
 pub struct TextureAssets {
 	#[asset(path = "textures/background.png")]
     pub background: Handle<Image>,
@@ -56,18 +61,26 @@ pub struct TextureAssets {
     pub github: Handle<Image>,
 	#[asset(path = "textures/hoop.png")]
     pub hoop: Handle<Image>,
-	#[asset(path = "textures/atom.png")]
-    pub atom: Handle<Image>,
-	#[asset(path = "textures/plus.png")]
-    pub plus: Handle<Image>,
 	#[asset(path = "textures/rodney.png")]
 	pub rodney: Handle<Image>,
-	#[asset(path = "textures/star.png")]
-    pub star: Handle<Image>,
 	#[asset(path = "textures/triangle.png")]
     pub triangle: Handle<Image>,
 	#[asset(path = "textures/squiggle.png")]
     pub squiggle: Handle<Image>,
-	#[asset(path = "textures/weapon.png")]
-    pub weapon: Handle<Image>,
+	/// Indexed like [`crate::registry::MOLECULE_DEFINITIONS`], registered under [`crate::registry::MOLECULE_SPRITES_KEY`]
+	#[asset(key = "molecule_sprites", collection(typed))]
+	pub molecules: Vec<Handle<Image>>,
+}
+
+#[derive(AssetCollection, Resource)]
+pub struct DataAssets {
+	#[asset(path = "data/molecules.reactions.ron")]
+	pub reactions: Handle<ReactionTable>,
+	#[asset(path = "levels/default.level.ron")]
+	pub level: Handle<Level>,
+	/// Shows off obstacles and pickups, only reachable from the editor
+	#[asset(path = "levels/sample.level.ron")]
+	pub sample_level: Handle<Level>,
+	#[asset(path = "data/default.weapons.ron")]
+	pub weapons: Handle<WeaponSet>,
 }
@@ -0,0 +1,167 @@
+use bevy::prelude::*;
+use bevy::sprite::Anchor;
+
+use crate::arena::Arena;
+use crate::audio::{PlaySfx, Sfx};
+use crate::camera::CameraShake;
+use crate::molecules::{MoleculeInfo, ReactorHit};
+use crate::player::PlayerInfo;
+use crate::simulation::SimulationSet;
+use crate::GameState;
+
+/// Heat at which the reactor melts down
+pub const MAX_HEAT: f32 = 100.0;
+/// Heat added each second by every molecule in the room
+const HEAT_PER_MOLECULE: f32 = 0.02;
+/// Heat added by a molecule striking the reactor, per unit of speed it hit with
+const HEAT_PER_HIT_SPEED: f32 = 0.01;
+/// Heat added by each reaction between molecules
+const REACTION_HEAT: f32 = 0.5;
+/// Most heat reactions can add each second, reactions in a packed room grow with the square of the molecules in it
+const MAX_REACTION_HEATING: f32 = 5.0;
+/// Heat added by two molecules bouncing off each other, per unit of speed they closed at
+const HEAT_PER_COLLISION_SPEED: f32 = 0.002;
+/// Most heat collisions can add each second, for the same reason as reactions
+const MAX_COLLISION_HEATING: f32 = 3.0;
+/// Heat taken away by each molecule the player clears
+pub const CLEAR_COOLING: f32 = 1.0;
+
+const METER_WIDTH: f32 = 160.0;
+const METER_HEIGHT: f32 = 12.0;
+/// Height of the meter above the bottom of the room, level with the score
+const METER_RAISE: f32 = 32.0;
+const COOL_COLOUR: Color = Color::linear_rgb(0.3, 0.75, 0.45);
+const HOT_COLOUR: Color = Color::linear_rgb(0.9, 0.2, 0.1);
+
+pub struct MeltdownPlugin;
+
+/// This plugin heats the reactor up as molecules fill the room, collide and react, and ends the run in a meltdown when it overheats
+/// The heat is shown on a meter next to the score
+impl Plugin for MeltdownPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(OnEnter(GameState::Playing), (reset_heat, spawn_heat_meter))
+			.add_systems(FixedUpdate, heat_reactor.in_set(SimulationSet::Reactor))
+			.add_systems(Update, update_heat_meter.run_if(in_state(GameState::Playing)))
+			.add_systems(OnExit(GameState::Playing), despawn_heat_meter)
+			;
+	}
+}
+
+/// How hot the reactor is, from `0.0` up to [`MAX_HEAT`]
+#[derive(Resource, Default)]
+pub struct ReactorHeat {
+	heat: f32,
+	/// Reactions since the reactor was last heated
+	reactions: u32,
+	/// Summed closing speed of the molecule collisions since the reactor was last heated
+	collision_speed: f32,
+	melted_down: bool,
+}
+
+impl ReactorHeat {
+	pub fn add(&mut self, amount: f32) {
+		self.heat = (self.heat + amount).clamp(0.0, MAX_HEAT);
+	}
+
+	/// Counts a reaction between molecules toward the heat added this tick
+	pub fn add_reaction(&mut self) {
+		self.reactions += 1;
+	}
+
+	/// Counts two molecules bouncing off each other at `speed` toward the heat added this tick
+	pub fn add_collision(&mut self, speed: f32) {
+		self.collision_speed += speed;
+	}
+
+	pub fn fraction(&self) -> f32 {
+		self.heat / MAX_HEAT
+	}
+
+	/// Whether the run ended because the reactor overheated
+	pub fn melted_down(&self) -> bool {
+		self.melted_down
+	}
+}
+
+#[derive(Component)]
+struct HeatMeter;
+
+#[derive(Component)]
+struct HeatMeterFill;
+
+fn reset_heat(mut commands: Commands) {
+	commands.insert_resource(ReactorHeat::default());
+}
+
+fn heat_reactor(
+	mut heat: ResMut<ReactorHeat>,
+	mut reactor_hits: EventReader<ReactorHit>,
+	mut player_query: Query<&mut PlayerInfo>,
+	molecule_query: Query<(), With<MoleculeInfo>>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut shake: EventWriter<CameraShake>,
+	time: Res<Time>,
+) {
+	let hit_heat: f32 = reactor_hits.read().map(|hit| hit.speed * HEAT_PER_HIT_SPEED).sum();
+	let reaction_heat = (heat.reactions as f32 * REACTION_HEAT).min(MAX_REACTION_HEATING * time.delta_secs());
+	heat.reactions = 0;
+	let collision_heat = (heat.collision_speed * HEAT_PER_COLLISION_SPEED).min(MAX_COLLISION_HEATING * time.delta_secs());
+	heat.collision_speed = 0.0;
+	heat.add(hit_heat + reaction_heat + collision_heat + molecule_query.iter().count() as f32 * HEAT_PER_MOLECULE * time.delta_secs());
+
+	let mut p_info = player_query.single_mut().expect("Could not find player");
+	if heat.heat >= MAX_HEAT && p_info.lives > 0.0 {
+		// Ends the run the same way losing the last life does
+		heat.melted_down = true;
+		p_info.lives = 0.0;
+		sfx.write(PlaySfx {sfx: Sfx::RadiationHit, volume: 0.4, playback_rate: 0.5});
+		shake.write(CameraShake(1.0));
+	}
+}
+
+fn spawn_heat_meter(mut commands: Commands, arena: Res<Arena>) {
+	commands.spawn((
+		Transform::from_xyz(arena.floor().max.x - METER_WIDTH, -arena.size.y / 2.0 + METER_RAISE, 200.0),
+		Visibility::default(),
+		HeatMeter,
+	)).with_children(|meter| {
+		meter.spawn((
+			Text2d::new("Reactor Heat"),
+			Transform::from_xyz(0.0, 14.0, 0.0),
+		));
+		meter.spawn((
+			Sprite::from_color(Color::linear_rgb(0.15, 0.15, 0.15), Vec2::new(METER_WIDTH, METER_HEIGHT)),
+			Transform::from_xyz(0.0, -12.0, 0.0),
+		));
+		meter.spawn((
+			Sprite {
+				color: COOL_COLOUR,
+				custom_size: Some(Vec2::new(0.0, METER_HEIGHT)),
+				anchor: Anchor::CenterLeft,
+				..default()
+			},
+			Transform::from_xyz(-METER_WIDTH / 2.0, -12.0, 1.0),
+			HeatMeterFill,
+		));
+	});
+}
+
+fn update_heat_meter(
+	heat: Res<ReactorHeat>,
+	mut fill_query: Query<&mut Sprite, With<HeatMeterFill>>,
+) {
+	for mut sprite in fill_query.iter_mut() {
+		let fraction = heat.fraction();
+		sprite.custom_size = Some(Vec2::new(METER_WIDTH * fraction, METER_HEIGHT));
+		sprite.color = COOL_COLOUR.mix(&HOT_COLOUR, fraction);
+	}
+}
+
+fn despawn_heat_meter(
+	mut commands: Commands,
+	meter_query: Query<Entity, With<HeatMeter>>,
+) {
+	for entity in meter_query.iter() {
+		commands.entity(entity).despawn();
+	}
+}
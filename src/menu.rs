@@ -1,6 +1,6 @@
+use crate::leaderboard::{spawn_leaderboard_panel, LeaderboardPanel};
 use crate::loading::TextureAssets;
 use crate::GameState;
-use crate::postprocess::PostProcessSettings;
 
 use bevy::prelude::*;
 
@@ -10,7 +10,8 @@ pub struct MenuPlugin;
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), (setup_menu, spawn_background))
+        app.add_systems(OnExit(GameState::Loading), spawn_background)
+            .add_systems(OnEnter(GameState::Menu), setup_menu)
             .add_systems(Update, click_play_button.run_if(in_state(GameState::Menu)))
             .add_systems(OnExit(GameState::Menu), cleanup_menu)
 			;
@@ -65,21 +66,7 @@ impl Default for ButtonColors {
 #[derive(Component)]
 struct Menu;
 
-#[derive(Component)]
-pub struct MainCamera;
-
 fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
-	commands.spawn((
-        Camera2d,
-		Transform::from_xyz(0.0, 0.0, 1000.0),
-        MainCamera,
-        PostProcessSettings {
-            intensity: 0.025,
-            scanline_freq: 202.5,
-            line_intensity: 0.1,
-            ..default()
-        },
-    ));
     commands.spawn((
 		Sprite {
             image: textures.ditheredbackground.clone(),
@@ -180,6 +167,152 @@ fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>) {
 				TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
 			));
 	});
+    commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Percent(85.0),
+			width: Val::Px(130.0),
+			height: Val::Px(50.0),
+			margin: UiRect {
+				left: Val::Px(131.0),
+				top: Val::Px(-25.0),
+				..default()
+			},
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			..default()
+		},
+		Menu,
+	))
+	.with_children(|children| {
+		let button_colors = ButtonColors::default();
+		children
+			.spawn((
+				Button,
+				Node {
+					width: Val::Px(130.0),
+					height: Val::Px(50.0),
+					border: UiRect::all(Val::Px(2.0)),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..Default::default()
+				},
+				BorderColor(Color::linear_rgb(0.4, 0.64, 0.72)),
+				BackgroundColor(button_colors.normal),
+				button_colors,
+				ChangeState(GameState::Editor),
+			))
+			.with_child((
+				Text::new("EDITOR"),
+				TextFont {
+					font_size: 25.0,
+					..default()
+				},
+				TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+			));
+	});
+    commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Percent(85.0),
+			width: Val::Px(130.0),
+			height: Val::Px(50.0),
+			margin: UiRect {
+				left: Val::Px(-261.0),
+				top: Val::Px(-25.0),
+				..default()
+			},
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			..default()
+		},
+		Menu,
+	))
+	.with_children(|children| {
+		let button_colors = ButtonColors::default();
+		children
+			.spawn((
+				Button,
+				Node {
+					width: Val::Px(130.0),
+					height: Val::Px(50.0),
+					border: UiRect::all(Val::Px(2.0)),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..Default::default()
+				},
+				BorderColor(Color::linear_rgb(0.4, 0.64, 0.72)),
+				BackgroundColor(button_colors.normal),
+				button_colors,
+				ToggleLeaderboard,
+			))
+			.with_child((
+				Text::new("SCORES"),
+				TextFont {
+					font_size: 25.0,
+					..default()
+				},
+				TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+			));
+	});
+    commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			right: Val::Px(20.0),
+			top: Val::Px(20.0),
+			width: Val::Px(150.0),
+			height: Val::Px(50.0),
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			..default()
+		},
+		Menu,
+	))
+	.with_children(|children| {
+		let button_colors = ButtonColors::default();
+		children
+			.spawn((
+				Button,
+				Node {
+					width: Val::Px(150.0),
+					height: Val::Px(50.0),
+					border: UiRect::all(Val::Px(2.0)),
+					justify_content: JustifyContent::Center,
+					align_items: AlignItems::Center,
+					..Default::default()
+				},
+				BorderColor(Color::linear_rgb(0.4, 0.64, 0.72)),
+				BackgroundColor(button_colors.normal),
+				button_colors,
+				ChangeState(GameState::Settings),
+			))
+			.with_child((
+				Text::new("SETTINGS"),
+				TextFont {
+					font_size: 25.0,
+					..default()
+				},
+				TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+			));
+	});
+	// Covers the title, so it stays hidden until asked for
+	spawn_leaderboard_panel(
+		&mut commands,
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Px(120.0),
+			width: Val::Px(520.0),
+			margin: UiRect::left(Val::Px(-260.0)),
+			..default()
+		},
+		(Visibility::Hidden, Menu),
+	);
     commands
         .spawn((
             Node {
@@ -288,8 +421,13 @@ struct ChangeState(GameState);
 #[derive(Component)]
 struct OpenLink(&'static str);
 
+/// Shows or hides the leaderboard
+#[derive(Component)]
+struct ToggleLeaderboard;
+
 fn click_play_button(
     mut next_state: ResMut<NextState<GameState>>,
+    mut leaderboard_query: Query<&mut Visibility, With<LeaderboardPanel>>,
     mut interaction_query: Query<
         (
             &Interaction,
@@ -297,15 +435,20 @@ fn click_play_button(
             &ButtonColors,
             Option<&ChangeState>,
             Option<&OpenLink>,
+            Has<ToggleLeaderboard>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, button_colors, change_state, open_link) in &mut interaction_query {
+    for (interaction, mut color, button_colors, change_state, open_link, toggle_leaderboard) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 if let Some(state) = change_state {
                     next_state.set(state.0.clone());
+                } else if toggle_leaderboard {
+                    for mut visibility in leaderboard_query.iter_mut() {
+                        visibility.toggle_visible_hidden();
+                    }
                 } else if let Some(link) = open_link {
                     if let Err(error) = webbrowser::open(link.0) {
                         warn!("Failed to open link {error:?}");
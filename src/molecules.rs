@@ -1,18 +1,57 @@
 use std::f32::consts::PI;
 
 use bevy::prelude::*;
-use bevy::window::PrimaryWindow;
-use bevy_kira_audio::{Audio, AudioControl};
+use crate::arena::Arena;
+use crate::audio::{PlaySfx, Sfx};
+use crate::camera::CameraShake;
+use crate::highscores::HighScore;
+use crate::editor::PlayTest;
 use crate::GameState;
-use crate::player::{PlayerInfo, WeaponCollider, WeaponPivot};
-use crate::loading::{AudioAssets, TextureAssets};
+use crate::player::{PlayerInfo, WeaponMode, WeaponPivot, WeaponSweep};
+use crate::loading::{DataAssets, TextureAssets};
+use crate::meltdown::{ReactorHeat, CLEAR_COOLING};
+use crate::levels::Level;
+use crate::obstacles::{collide_with_obstacles, update_wave_obstacles, Shape};
+use crate::weapons::update_wave_pickups;
+use crate::reactions::{ReactionInfo, ReactionOutcome, ReactionTable};
+use crate::registry::{MoleculeKind, MoleculeRegistry};
+use crate::simulation::{GameRng, SimulationSet};
+use crate::spatial_hash::SpatialHash;
+
+const BULLET_COLOUR: Color = Color::hsv(27.0, 0.47, 0.84);
+const PARRIED_COLOUR: Color = Color::hsv(190.0, 0.35, 0.95);
+/// How fast a parried bullet flies back
+const PARRIED_SPEED: f32 = 480.0;
+const REACTOR_COLOUR: Color = Color::linear_rgb(0.3, 0.75, 0.45);
+/// How quickly the reactor's flash fades after a hit, per second
+const REACTOR_GLOW_DECAY: f32 = 4.0;
+/// Speed of a hit that flashes the reactor at full strength
+const REACTOR_GLOW_SPEED: f32 = 300.0;
+/// How heavy the weapon is when batting, heavier molecules are knocked away slower
+const BAT_MASS: f32 = 16.0;
+/// Fastest a molecule can be batted, any faster and it could pass through others between ticks
+const MAX_BAT_SPEED: f32 = 700.0;
+/// How long after being batted a molecule's reactions, and those of what it makes, still score
+const BATTED_DURATION: f32 = 2.0;
+/// Speed a bullet homes in on the player at
+const BULLET_SPEED: f32 = 120.0;
+/// How quickly a bullet's bounce off an obstacle fades, per second, before it is homing in again
+const BULLET_BOUNCE_DECAY: f32 = 3.0;
+/// How far the shockwave of a fully charged swing spreads and how long it takes
+const SHOCKWAVE_RADIUS: f32 = 220.0;
+const SHOCKWAVE_DURATION: f32 = 0.35;
+/// Largest molecule a shockwave destroys, bigger ones are left for the weapon
+const SHOCKWAVE_MAX_MOLECULE_RADIUS: f32 = 16.0;
+const SHOCKWAVE_COLOUR: Color = Color::linear_rgb(1.0, 0.55, 0.15);
 
 #[derive(Component)]
 pub struct MoleculeInfo {
 	pub vel: Vec2,
-	pub index: usize,
+	pub kind: MoleculeKind,
 	pub reacted: bool,
 	pub reaction_cooldown: f32,
+	/// Seconds left that its reactions score, after being batted or made by a batted molecule
+	pub batted: f32,
 	pub radius: f32,
 	pub mass: f32,
 	pub spawn_growth: f32,
@@ -20,7 +59,35 @@ pub struct MoleculeInfo {
 
 #[derive(Component)]
 pub struct BulletInfo{
-	radius: f32,
+	pub radius: f32,
+	/// Where the reaction that fired it happened
+	origin: Vec2,
+	/// Velocity once parried, it then flies straight and only hurts molecules
+	parried: Option<Vec2>,
+	/// Velocity it last moved with
+	vel: Vec2,
+	/// Velocity from bouncing off obstacles, added to its homing until it fades
+	bounce: Vec2,
+}
+
+impl BulletInfo {
+	pub fn parried(&self) -> bool {
+		self.parried.is_some()
+	}
+
+	/// Bounces the bullet off a surface facing `normal`, if it is heading into it
+	pub fn reflect(&mut self, normal: Vec2) {
+		let into = self.vel.dot(normal);
+		if into >= 0.0 {
+			return;
+		}
+		let change = -2.0 * into * normal;
+		self.vel += change;
+		match &mut self.parried {
+			Some(vel) => *vel = self.vel,
+			None => self.bounce += change,
+		}
+	}
 }
 
 pub struct MoleculesPlugin;
@@ -28,57 +95,93 @@ pub struct MoleculesPlugin;
 impl Plugin for MoleculesPlugin {
 	fn build(&self, app: &mut App) {
 		app
-			.add_systems(OnExit(GameState::Menu), spawn_score)
+			.add_systems(OnEnter(GameState::Playing), spawn_score)
+			.add_systems(OnEnter(GameState::Menu), despawn_score)
 			.add_systems(Update, update_score.run_if(in_state(GameState::Playing)))
 			.add_systems(Update, update_highscore.run_if(in_state(GameState::Retry)))
 			.add_systems(OnEnter(GameState::Playing), spawn_reactor)
-			.add_systems(Update, (
-				// level_editor,
+			.add_systems(Update, glow_reactor.run_if(in_state(GameState::Playing)))
+			.add_systems(FixedUpdate, (
 				spawn_molecules,
+				update_wave_obstacles,
+				update_wave_pickups,
 				molecule_movement,
 				move_bullet,
+				strike_with_parried_bullets,
+				collide_with_obstacles,
+				collide_with_reactor,
 				clamp_inside_reactor,
 				destroy_molecules,
+				spread_shockwaves,
 				deal_with_particles,
-			).chain().run_if(in_state(GameState::Playing)));
+			).chain().in_set(SimulationSet::Molecules));
 	}
 }
 
 #[derive(Component)]
-pub struct Reactor;
+pub struct Reactor {
+	pub radius: f32,
+}
+
+/// Flash on the reactor's sprite in the colour of the last molecule to hit it, fading after each hit
+#[derive(Component)]
+struct ReactorGlow {
+	colour: Color,
+	amount: f32,
+}
+
+/// Sent by gameplay when a molecule bounces off the reactor, for anything that reacts to the reactor being struck
+#[derive(Event, Clone, Copy, Debug)]
+pub struct ReactorHit {
+	pub kind: MoleculeKind,
+	/// How fast the molecule was heading into the reactor
+	pub speed: f32,
+}
 
 #[derive(Resource)]
 pub struct SpawnTracker {
 	timer: f32,
 	increment: usize,
-	times: Vec<Vec<f32>>,
-	indices: Vec<Vec<usize>>,
-	velocities: Vec<Vec<f32>>,
-	angles: Vec<Vec<f32>>,
-	track_player: Vec<Vec<bool>>,
-	level: usize,
-	level_lengths: Vec<usize>,
+	level: Handle<Level>,
+	wave: usize,
 }
 
-#[derive(Component)]
-pub struct Score{
-	pub highscore: f32,
-	pub hightime: f32,
+impl SpawnTracker {
+	pub fn level(&self) -> &Handle<Level> {
+		&self.level
+	}
+
+	/// Index of the wave being played
+	pub fn wave(&self) -> usize {
+		self.wave
+	}
 }
 
+#[derive(Component)]
+pub struct Score;
+
 fn spawn_score(
 	mut commands: Commands,
+	score_query: Query<(), With<Score>>,
 ) {
+	// The score stays up to show the highscore on the retry screen, so it is only spawned once
+	if !score_query.is_empty() {return};
 	commands.spawn((
 		Text2d::new("Score: 0\nTime Survived: 0"),
 		Transform::from_xyz(0.0, -405.0 + 32.0, 200.0),
-		Score{
-			highscore: 0.0,
-			hightime: 0.0,
-		},
+		Score,
 	));
 }
 
+fn despawn_score(
+	mut commands: Commands,
+	score_query: Query<Entity, With<Score>>,
+) {
+	for entity in score_query.iter() {
+		commands.entity(entity).despawn();
+	}
+}
+
 fn update_score(
 	mut score_query: Query<&mut Text2d, With<Score>>,
 	player_query: Query<&PlayerInfo>,
@@ -87,7 +190,7 @@ fn update_score(
 	let mut score = score_query.single_mut().expect("Could not find score");
 	if player.lives > 0.0 {
 		let time_surv = if player.time_survived < 59.0 {format!{"{:.2}s", player.time_survived % 60.0}}
-		else if player.time_survived >= 59.0 && player.time_survived < 60.0 {format!{"59s"}}
+		else if player.time_survived >= 59.0 && player.time_survived < 60.0 {"59s".to_string()}
 		else {
 			format!{"{:.0}m {:.0}s", (player.time_survived/60.0).floor() % 60.0, player.time_survived.floor() % 60.0}
 		};
@@ -96,223 +199,86 @@ fn update_score(
 }
 
 fn update_highscore(
-	mut score_query: Query<(&mut Text2d, &Score)>,
+	mut score_query: Query<&mut Text2d, With<Score>>,
+	highscore: Res<HighScore>,
 ) {
-	let (mut text, score) = score_query.single_mut().expect("Could not find score");
-	let time_surv = if score.hightime < 59.0 {format!{"{:.2}s", score.hightime % 60.0}} 
-	else if score.hightime >= 59.0 && score.hightime < 60.0 {format!{"59s"}}
+	let mut text = score_query.single_mut().expect("Could not find score");
+	let time_surv = if highscore.time < 59.0 {format!{"{:.2}s", highscore.time % 60.0}} 
+	else if highscore.time >= 59.0 && highscore.time < 60.0 {"59s".to_string()}
 	else {
-		format!{"{:.0}m {:.0}s", (score.hightime/60.0).floor() % 60.0, score.hightime.floor() % 60.0}
+		format!{"{:.0}m {:.0}s", (highscore.time/60.0).floor() % 60.0, highscore.time.floor() % 60.0}
 	};
-	text.0 = format!("Highscore: {}\nLongest Time Survived: {}", score.highscore, time_surv);
+	text.0 = format!("Highscore: {}\nLongest Time Survived: {}", highscore.score, time_surv);
 }
 
 fn spawn_reactor(
 	mut commands: Commands,
 	textures: Res<TextureAssets>,
+	data: Res<DataAssets>,
+	play_test: Option<Res<PlayTest>>,
+	levels: Res<Assets<Level>>,
 ) {
+	let handle = PlayTest::level(play_test.as_deref(), &data);
+	let level = levels.get(handle).expect("Could not find level");
 	commands.spawn((Sprite {
 		image: textures.hoop.clone(),
-		custom_size: Some(Vec2::new(128.0, 128.0)),
+		color: REACTOR_COLOUR,
+		custom_size: Some(Vec2::splat(level.reactor_radius * 2.0)),
 		..default()
 	},
-	Transform::from_translation(Vec3::new(0.0, 0.0, 5.0)),
-	Visibility::Hidden,
-	Reactor,
+	Transform::from_translation(Vec3::new(0.0, 0.0, 0.5)),
+	Reactor {
+		radius: level.reactor_radius,
+	},
+	ReactorGlow {
+		colour: Color::WHITE,
+		amount: 0.0,
+	},
 	));
 
-	// let mut times = 			vec![1.0,   3.0,   4.0,   5.0,   5.1,   5.2,   5.3,   5.4,   5.5 ];
-	// let mut indices = 		vec![4,     4,     0,     0,     0,     0,     0,     0,     0];
-	// let mut angles = 			vec![0.0,   180.0, 180.0, 361.0, 361.0, 361.0, 361.0, 361.0, 361.0];
-	// let mut velocities = 		vec![100.0, 150.0, 400.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0];
-
-	// let mut times = 			vec![1.0];
-	// let mut indices = 		vec![0];
-	// let mut angles = 			vec![361.0];
-	// let mut velocities = 		vec![260.0];
-
-	// let mut times = vec![vec![]];
-	// let mut indices = vec![vec![]];
-	// let mut velocities = vec![vec![]];
-	// let mut track_player = vec![vec![]];
-
-	let times = vec![
-		vec![1.0, 4.0],
-		vec![0.0, 0.5, 1.0, 1.5, 2.0, 2.5, 3.0, 3.5, 8.0],
-		vec![0.0, 2.0, 4.0, 6.0, 10.0, 12.0],
-		vec![0.0, 0.5, 3.0, 3.5, 6.0, 6.5, 9.0, 9.5, 14.0],
-		vec![0.0, 2.0, 4.0, 5.0, 6.0, 7.0, 8.0, 8.5, 9.0, 9.5, 10.0, 15.0],
-		vec![0.0, 0.4, 0.8, 1.2, 1.6, 2.0, 2.4, 2.8, 5.0],
-		vec![0.0, 1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, 8.0, 9.0, 10.0, 15.0],
-	];
-	let indices = vec![
-		vec![4, 100],
-		vec![0, 0, 0, 0, 0, 0, 0, 0, 100],
-		vec![4, 3, 2, 1, 0, 100],
-		vec![4, 0, 3, 0, 2, 0, 1, 0, 100],
-		vec![2, 2, 2, 2, 0, 0, 0, 0, 0, 0, 0, 100],
-		vec![1, 1, 1, 1, 1, 1, 1, 1, 100],
-		vec![4, 4, 3, 3, 2, 2, 1, 1, 0, 0, 0, 100],
-	];
-	let velocities = vec![
-		vec![200.0, 0.0],
-		vec![260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 0.0],
-		vec![150.0, 160.0, 170.0, 180.0, 250.0, 0.0],
-		vec![200.0, 300.0, 200.0, 300.0, 200.0, 300.0, 200.0, 300.0, 0.0],
-		vec![260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 0.0],
-		vec![220.0, 220.0, 220.0, 220.0, 220.0, 220.0, 220.0, 220.0, 0.0],
-		vec![260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 260.0, 0.0]
-	];
-	let angles = vec![
-		vec![0.0, 0.0],
-		vec![0.0, 45.0, 90.0, 135.0, 180.0, 225.0, 270.0, 315.0, 0.0],
-		vec![0.0, 90.0, 180.0, 270.0, 0.0, 0.0],
-		vec![180.0, 180.0, 0.0, 0.0, 270.0, 270.0, 45.0, 45.0, 0.0],
-		vec![0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
-		vec![0.0, 315.0, 270.0, 225.0, 180.0, 135.0, 90.0, 45.0, 0.0],
-		vec![0.0, 180.0, 270.0, 90.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0, 0.0],
-	];
-	let track_player = vec![
-		vec![false, false],
-		vec![false, false, false, false, false, false, false, false, false],
-		vec![false, false, false, false, true, false],
-		vec![false, false, false, false, false, false, false, false, false],
-		vec![true, true, true, true, true, true, true, true, true, true, true, true],
-		vec![false, false, false, false, false, false, false, false, false],
-		vec![false, false, false, false, true, true, true, true, true, true, true, false],
-	];
-
-	let mut level_lengths = vec![];
-	for i in 0..times.len() {
-		level_lengths.push(times[i].len());
-	}
-
 	commands.insert_resource(SpawnTracker{
 		timer: 0.0,
 		increment: 0,
-		times: times,
-		indices: indices,
-		velocities: velocities,
-		angles: angles,
-		track_player: track_player,
-		level: 0,
-		level_lengths: level_lengths,
+		level: handle.clone(),
+		wave: 0,
 	});
 }
 
-fn level_editor(
-	mut current_time: Local<f32>,
-	mut spawn_tracker: ResMut<SpawnTracker>,
-	windows: Query<&Window, With<PrimaryWindow>>,
-	keys: Res<ButtonInput<KeyCode>>,
-	time: Res<Time>,
-) {
-	if *current_time == 0.0 {
-		spawn_tracker.level = spawn_tracker.times.len();
-	}
-	*current_time += time.delta_secs();
-	let window = windows.single().expect("Could not find window");
-	let window_size = Vec2::new(window.width(), window.height());
-	if let Some(mut target) = window.cursor_position() {
-		target -= window_size / 2.0;
-		target.y = -target.y;
-		let velocity = target.length();
-		let angle = target.normalize().to_angle();
-		let level = spawn_tracker.level;
-		let tracked = if keys.pressed(KeyCode::Space) {true} else {false};
-		if keys.just_pressed(KeyCode::Digit0) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(0);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::Digit1) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(1);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::Digit2) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(2);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::Digit3) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(3);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::Digit4) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(4);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::Digit5) {
-			spawn_tracker.times[level].push(*current_time);
-			spawn_tracker.indices[level].push(5);
-			spawn_tracker.velocities[level].push(velocity);
-			spawn_tracker.velocities[level].push(angle);
-			spawn_tracker.track_player[level].push(tracked);
-		}
-		if keys.just_pressed(KeyCode::KeyP) {
-			println!("{:?}\n{:?}\n{:?}\n{:?}\n{:?}", spawn_tracker.times, spawn_tracker.indices, spawn_tracker.velocities, spawn_tracker.angles, spawn_tracker.track_player);
-		}
-		if keys.just_pressed(KeyCode::KeyL) {
-			spawn_tracker.level += 1;
-			spawn_tracker.times.push(vec![]);
-			spawn_tracker.indices.push(vec![]);
-			spawn_tracker.velocities.push(vec![]);
-			spawn_tracker.track_player.push(vec![]);
-		}
-	}
-
-}
-
-fn rand_vel() -> Vec2 {
-	Vec2::new(rand::random::<f32>() - 0.5, rand::random::<f32>() - 0.5).normalize() * 260.0
+fn rand_vel(rng: &mut GameRng) -> Vec2 {
+	Vec2::new(rng.random::<f32>() - 0.5, rng.random::<f32>() - 0.5).normalize() * 260.0
 }
 
-fn _rand_pos() -> Vec3 {
-	(Vec2::new((rand::random::<f32>() - 0.5) * 1080.0, (rand::random::<f32>() - 0.5) * 810.0).clamp_length_min(128.0)).extend(0.0)
+fn _rand_pos(rng: &mut GameRng) -> Vec3 {
+	(Vec2::new((rng.random::<f32>() - 0.5) * 1080.0, (rng.random::<f32>() - 0.5) * 810.0).clamp_length_min(128.0)).extend(0.0)
 }
 
 fn spawn_molecules(
 	mut commands: Commands,
 	mut spawn_tracker: ResMut<SpawnTracker>,
+	levels: Res<Assets<Level>>,
 	reactor_query: Query<&Transform, With<Reactor>>,
 	player_query: Query<&Transform, (Without<Reactor>, With<PlayerInfo>)>,
-	textures: Res<TextureAssets>,
+	registry: Res<MoleculeRegistry>,
 	time: Res<Time>,
 ) {
-	spawn_tracker.timer += time.delta_secs() * (1.0 + spawn_tracker.level as f32/10.0);
-	if spawn_tracker.timer > spawn_tracker.times[spawn_tracker.level][spawn_tracker.increment] {
-		let reactor = reactor_query.single().expect("Could not find reactor");
-		let player = player_query.single().expect("Could not find player");
-		let pos = Vec2::new(reactor.translation.x, reactor.translation.y - 48.0).extend(1.0);
-		let index = spawn_tracker.indices[spawn_tracker.level][spawn_tracker.increment];
-		let angle = if spawn_tracker.track_player[spawn_tracker.level][spawn_tracker.increment] {(player.translation.xy() - pos.xy()).normalize()}
-			else {Vec2::from_angle((-spawn_tracker.angles[spawn_tracker.level][spawn_tracker.increment]).to_radians()).rotate(Vec2::from_angle(90.0_f32.to_radians()))};
-		let velocity = spawn_tracker.velocities[spawn_tracker.level][spawn_tracker.increment];
-		spawn_molecule(&mut commands, &textures, pos, angle * velocity, index, get_molecule_radius(index), get_molecule_mass(index));
-		if spawn_tracker.increment == spawn_tracker.level_lengths[spawn_tracker.level] - 1 {
-			spawn_tracker.increment = 0;
-			spawn_tracker.timer = 0.0;
-			if spawn_tracker.level == spawn_tracker.level_lengths.len() - 1 {
-				spawn_tracker.level = 0;
-			} else {
-				spawn_tracker.level += 1;
-			}
-		} else {
+	let level = levels.get(&spawn_tracker.level).expect("Could not find level");
+	let wave = &level.waves[spawn_tracker.wave];
+	spawn_tracker.timer += time.delta_secs() * (1.0 + spawn_tracker.wave as f32/10.0);
+	if let Some(record) = wave.spawns.get(spawn_tracker.increment) {
+		if spawn_tracker.timer > record.time {
+			let reactor = reactor_query.single().expect("Could not find reactor");
+			let player = player_query.single().expect("Could not find player");
+			let centre = reactor.translation.xy();
+			let angle = if record.track_player {(player.translation.xy() - centre).normalize()}
+				else {record.direction()};
+			let pos = (centre + level.spawn_offset(angle, registry.get(record.molecule).radius)).extend(1.0);
+			spawn_molecule(&mut commands, &registry, pos, angle * record.speed, record.molecule, 0.0);
 			spawn_tracker.increment += 1;
 		}
+	} else if spawn_tracker.timer > wave.duration {
+		spawn_tracker.increment = 0;
+		spawn_tracker.timer = 0.0;
+		spawn_tracker.wave = (spawn_tracker.wave + 1) % level.waves.len();
 	}
 }
 
@@ -338,30 +304,41 @@ fn deal_with_particles(
 	}
 }
 
+/// Ring sent out by a fully charged swing, destroying the small molecules it passes over
+#[derive(Component)]
+pub struct Shockwave {
+	age: f32,
+}
+
+pub fn spawn_shockwave(commands: &mut Commands, textures: &TextureAssets, position: Vec2) {
+	commands.spawn((
+		Sprite {
+			image: textures.hoop.clone(),
+			custom_size: Some(Vec2::ZERO),
+			color: SHOCKWAVE_COLOUR,
+			..default()
+		},
+		Transform::from_translation(position.extend(2.0)),
+		Shockwave { age: 0.0 },
+	));
+}
+
 fn spawn_particles(
 	commands: &mut Commands,
+	rng: &mut GameRng,
 	textures: &Res<TextureAssets>,
 	loc: Vec2,
-	color_index: usize,
+	colour: Color,
 ) {
-	let colours = [
-		Color::hsv(32.0, 0.14, 0.77),
-		Color::hsv(60.0, 0.82, 0.45),
-		Color::hsv(53.0, 0.88, 0.74),
-		Color::hsv(10.0, 0.77, 0.75),
-		Color::hsv(354.0, 0.45, 0.80),
-		Color::hsv(27.0, 0.47, 0.84),
-		Color::hsv(281.0, 0.53, 0.32),
-	];
 	for _ in 0..8 {
-		let velocity = Vec2::new(0.0, 1.0 + rand::random::<f32>())
-			.rotate(Vec2::from_angle(rand::random::<f32>() * 2.0 * PI));
+		let velocity = Vec2::new(0.0, 1.0 + rng.random::<f32>())
+			.rotate(Vec2::from_angle(rng.random::<f32>() * 2.0 * PI));
 		let angle = velocity.y.atan2(velocity.x);
 		commands.spawn((
 			Sprite {
 				image: textures.squiggle.clone(),
 				custom_size: Some(Vec2::new(20.0, 20.0)),
-				color: colours[color_index],
+				color: colour,
 				..default()
 			},
 			Transform {
@@ -377,31 +354,14 @@ fn spawn_particles(
 	}
 }
 
-fn spawn_molecule(commands: &mut Commands, textures: &Res<TextureAssets>, pos: Vec3, vel: Vec2, index: usize, radius: f32, mass: f32) {
-	if index == 100 {return};
-	let colours = [
-		Color::hsv(32.0, 0.14, 0.77),
-		Color::hsv(60.0, 0.82, 0.45),
-		Color::hsv(53.0, 0.88, 0.74),
-		Color::hsv(10.0, 0.77, 0.75),
-		Color::hsv(354.0, 0.45, 0.80),
-		Color::hsv(27.0, 0.47, 0.84),
-		Color::hsv(281.0, 0.53, 0.32),
-	];
-	let colour = colours[index];
-
-	let sprites = [
-		textures.star.clone(),
-		textures.ball.clone(),
-		textures.plus.clone(),
-		textures.atom.clone(),
-		textures.hoop.clone(),
-	];
+fn spawn_molecule(commands: &mut Commands, registry: &MoleculeRegistry, pos: Vec3, vel: Vec2, kind: MoleculeKind, batted: f32) {
+	let info = registry.get(kind);
+	let radius = info.radius;
 
 	commands.spawn((
 		Sprite {
-			image: sprites[index.clamp(0, sprites.len()-1)].clone(),
-			color: colour,
+			image: info.sprite.clone(),
+			color: info.colour,
 			custom_size: Some(Vec2::new(radius * 2.0, radius * 2.0)),
 			..default()
 		},
@@ -411,16 +371,17 @@ fn spawn_molecule(commands: &mut Commands, textures: &Res<TextureAssets>, pos: V
 		},
 		MoleculeInfo {
 			vel,
-			index,
+			kind,
 			reacted: true,
 			reaction_cooldown: 0.25,
+			batted,
 			radius,
-			mass,
+			mass: info.mass,
 			spawn_growth: 0.0,
 		},
 	)).with_child(
 		Sprite {
-			image: sprites[index.clamp(0, sprites.len()-1)].clone(),
+			image: info.sprite.clone(),
 			color: Color::linear_rgb(1.0, 1.0, 1.0),
 			custom_size: Some(Vec2::new(radius * 2.0 + 6.0, radius * 2.0 + 6.0)),
 			..default()
@@ -447,21 +408,10 @@ fn spawn_cross(commands: &mut Commands, textures: &Res<TextureAssets>, index: f3
 }
 
 fn spawn_bullet(commands: &mut Commands, textures: &Res<TextureAssets>, pos: Vec3, radius: f32) {
-	let colours = [
-		Color::hsv(32.0, 0.14, 0.77),
-		Color::hsv(60.0, 0.82, 0.45),
-		Color::hsv(53.0, 0.88, 0.74),
-		Color::hsv(10.0, 0.77, 0.75),
-		Color::hsv(354.0, 0.45, 0.80),
-		Color::hsv(27.0, 0.47, 0.84),
-		Color::hsv(281.0, 0.53, 0.32),
-	];
-	let colour = colours[5];
-
 	commands.spawn((
 		Sprite {
 			image: textures.triangle.clone(),
-			color: colour,
+			color: BULLET_COLOUR,
 			custom_size: Some(Vec2::new(radius * 2.0, radius * 2.0)),
 			..default()
 		},
@@ -471,6 +421,10 @@ fn spawn_bullet(commands: &mut Commands, textures: &Res<TextureAssets>, pos: Vec
 		},
 		BulletInfo {
 			radius,
+			origin: pos.xy(),
+			parried: None,
+			vel: Vec2::ZERO,
+			bounce: Vec2::ZERO,
 		},
 	)).with_child(
 		Sprite {
@@ -479,23 +433,24 @@ fn spawn_bullet(commands: &mut Commands, textures: &Res<TextureAssets>, pos: Vec
 			custom_size: Some(Vec2::new(radius * 2.0 + 6.0, radius * 2.0 + 6.0)),
 			..default()
 		}
-	);;
+	);
 }
 
 fn take_damage(
 	entity: Entity,
 	p_info: &mut PlayerInfo,
-	mut commands: &mut Commands,
+	commands: &mut Commands,
 	textures: &Res<TextureAssets>,
-	audio: &Res<Audio>,
-	sfx: &Res<AudioAssets>,
+	sfx: &mut EventWriter<PlaySfx>,
+	shake: &mut EventWriter<CameraShake>,
 ) {
 	if p_info.invul_duration == 0.0 {
 		p_info.invul_duration = 1.0;
 		p_info.stun_duration = 0.4;
 		p_info.lives -= 1.0;
-		spawn_cross(&mut commands, &textures, p_info.lives as f32);
-		audio.play(sfx.radiation_hit.clone()).with_volume(0.25).with_playback_rate(1.0 - (2.0 - p_info.lives as f64) * 0.2);
+		spawn_cross(commands, textures, p_info.lives);
+		sfx.write(PlaySfx {sfx: Sfx::RadiationHit, volume: 0.25, playback_rate: 1.0 - (2.0 - p_info.lives as f64) * 0.2});
+		shake.write(CameraShake(0.6));
 	}
 	commands.entity(entity).despawn();
 }
@@ -503,76 +458,31 @@ fn take_damage(
 fn move_bullet(
 	mut commands: Commands,
 	mut player_query: Query<(&Transform, &mut PlayerInfo)>,
-	mut bullet_query: Query<(Entity, &mut Transform), (With<BulletInfo>, Without<PlayerInfo>)>,
+	mut bullet_query: Query<(Entity, &mut BulletInfo, &mut Transform), Without<PlayerInfo>>,
 	textures: Res<TextureAssets>,
-	audio: Res<Audio>,
-	sfx: Res<AudioAssets>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut shake: EventWriter<CameraShake>,
+	arena: Res<Arena>,
 	time: Res<Time>,
 ) {
 	let (p_transform, mut p_info) = player_query.single_mut().expect("Could not find player");
-	for (entity, mut b_transform) in bullet_query.iter_mut() {
+	for (entity, mut b_info, mut b_transform) in bullet_query.iter_mut() {
+		if let Some(vel) = b_info.parried {
+			b_transform.translation += (vel * time.delta_secs()).extend(0.0);
+			if !arena.bounds(b_info.radius).contains(b_transform.translation.xy()) {
+				commands.entity(entity).despawn();
+			}
+			continue;
+		}
 		let offset = p_transform.translation.xy() - b_transform.translation.xy();
 		if offset.length() < 6.0 + 24.0 {
-			take_damage(entity, &mut p_info, &mut commands, &textures, &audio, &sfx);
+			take_damage(entity, &mut p_info, &mut commands, &textures, &mut sfx, &mut shake);
 		} else {
-			b_transform.translation = (b_transform.translation.xy() + (120.0 * offset.normalize() * time.delta_secs())).extend(1.0);
-			b_transform.rotation = Quat::from_axis_angle(Vec3::Z, offset.to_angle() - 5.0*PI/4.0);
-		}
-	}
-}
-
-pub enum ReactionInfo {
-	Reaction(Vec<usize>),
-	None,
-}
-
-fn valid_molecule_combination(a: usize, b: usize) -> ReactionInfo {
-	let (a, b) = (a.min(b), a.max(b));
-	match a {
-		0 => match b {
-			0 => ReactionInfo::Reaction(vec![100, 101]),
-			1 => ReactionInfo::Reaction(vec![100, 0, 0, 0]),
-			2 => ReactionInfo::Reaction(vec![100, 1, 1, 0]),
-			3 => ReactionInfo::Reaction(vec![100, 2, 2, 0]),
-			4 => ReactionInfo::Reaction(vec![100, 3, 3, 0]),
-			_ => ReactionInfo::None,
-		}
-		1 => match b {
-			_ => ReactionInfo::None,
+			b_info.bounce *= (-BULLET_BOUNCE_DECAY * time.delta_secs()).exp();
+			b_info.vel = BULLET_SPEED * offset.normalize() + b_info.bounce;
+			b_transform.translation = (b_transform.translation.xy() + b_info.vel * time.delta_secs()).extend(1.0);
+			b_transform.rotation = Quat::from_axis_angle(Vec3::Z, b_info.vel.to_angle() - 5.0*PI/4.0);
 		}
-		2 => match b {
-			2 => ReactionInfo::Reaction(vec![100, 101, 0, 0, 0,]),
-			_ => ReactionInfo::None,
-		}
-		3 => match b {
-			_ => ReactionInfo::None,
-		}
-		4 => match b {
-			_ => ReactionInfo::None,
-		}
-		_ => ReactionInfo::None,
-	}
-}
-
-fn get_molecule_radius(index: usize) -> f32 {
-	match index {
-		0 => 10.0,
-		1 => 12.0,
-		2 => 16.0,
-		3 => 20.0,
-		4 => 24.0,
-		_ => 20.0,
-	}
-}
-
-fn get_molecule_mass(index: usize) -> f32 {
-	match index {
-		0 => 6.0,
-		1 => 8.0,
-		2 => 10.0,
-		3 => 12.0,
-		4 => 16.0,
-		_ => 20.0,
 	}
 }
 
@@ -581,53 +491,71 @@ fn molecule_movement(
 	mut molecule_query: Query<(Entity, &mut MoleculeInfo, &mut Transform), Without<PlayerInfo>>,
 	mut player_query: Query<(&mut PlayerInfo, &mut Transform)>,
 	textures: Res<TextureAssets>,
-	audio: Res<Audio>,
-	sfx: Res<AudioAssets>,
+	registry: Res<MoleculeRegistry>,
+	data: Res<DataAssets>,
+	reaction_tables: Res<Assets<ReactionTable>>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut shake: EventWriter<CameraShake>,
+	arena: Res<Arena>,
+	mut heat: ResMut<ReactorHeat>,
 	time: Res<Time>,
+	mut rng: ResMut<GameRng>,
+	mut spatial_hash: Local<SpatialHash>,
+	mut pairs: Local<Vec<(usize, usize)>>,
 ) {
-	let mut molecule_count = 0;
-	for _ in molecule_query.iter() {
-		molecule_count += 1;
-	}
-	let mut iter = molecule_query.iter_combinations_mut();
-	while let Some([
-		(entity_a, mut m_info_a, mut transform_a),
-		(entity_b, mut m_info_b, mut transform_b),
-	]) = iter.fetch_next()
-	{
+	let reactions = reaction_tables.get(&data.reactions).expect("Could not find reaction table");
+	let (mut p_info, p_transform) = player_query.single_mut().expect("Could not find player");
+	let (entities, positions): (Vec<Entity>, Vec<Vec2>) = molecule_query.iter()
+		.map(|(entity, _, transform)| (entity, transform.translation.xy()))
+		.unzip();
+	// Cells span twice the widest collision, so pairs nudged together by earlier pushes this frame are still found
+	spatial_hash.rebuild(4.0 * registry.max_radius(), &positions);
+	spatial_hash.candidate_pairs(&positions, &mut pairs);
+	for &(a, b) in pairs.iter() {
+		let Ok([
+			(entity_a, mut m_info_a, mut transform_a),
+			(entity_b, mut m_info_b, mut transform_b),
+		]) = molecule_query.get_many_mut([entities[a], entities[b]]) else {
+			continue;
+		};
 		if m_info_a.reacted || m_info_b.reacted {
 			continue;
 		}
 		let offset = transform_a.translation.xy() - transform_b.translation.xy();
-		if offset.length() <= m_info_a.radius + m_info_b.radius && molecule_count < 200 {
-			let info = valid_molecule_combination(m_info_a.index, m_info_b.index);
+		if offset.length() <= m_info_a.radius + m_info_b.radius {
+			let info = reactions.get(m_info_a.kind, m_info_b.kind);
 			match info {
 				ReactionInfo::None => (),
 				ReactionInfo::Reaction(products) => {
-					audio.play(sfx.ping.clone()).with_volume(0.4).with_playback_rate(0.75 + (rand::random::<f64>()/2.0));
+					sfx.write(PlaySfx {sfx: Sfx::Ping, volume: 0.4, playback_rate: 0.75 + (rng.random::<f64>()/2.0)});
 					if m_info_a.reaction_cooldown + m_info_b.reaction_cooldown == 0.0 {
 						m_info_a.reacted = true;
 						m_info_b.reacted = true;
 						m_info_a.reaction_cooldown = 0.25;
 						m_info_b.reaction_cooldown = 0.25;
-						for output in products {
-							let pos = (transform_b.translation.xy() + offset/2.0 + rand::random::<f32>()).extend(0.0);
-							if output < 100 {
-								let radius = get_molecule_radius(output);
-								let mass = get_molecule_mass(output);
-								spawn_molecule(&mut commands, &textures, pos, rand_vel(), output, radius, mass);
-							} else {
-								match output {
-									100 => {
-										commands.entity(entity_a).despawn();
-										spawn_particles(&mut commands, &textures, transform_a.translation.xy(), m_info_a.index);
-										commands.entity(entity_b).despawn();
-										spawn_particles(&mut commands, &textures, transform_b.translation.xy(), m_info_b.index);
-									}
-									101 => {
-										spawn_bullet(&mut commands, &textures, pos, 6.0);
+						heat.add_reaction();
+						// Reactions set off by the bat score for both molecules, and what they make carries on scoring
+						let batted = m_info_a.batted.max(m_info_b.batted);
+						if batted > 0.0 {
+							p_info.score += registry.get(m_info_a.kind).score + registry.get(m_info_b.kind).score;
+						}
+						for &output in products {
+							let pos = (transform_b.translation.xy() + offset/2.0 + rng.random::<f32>()).extend(0.0);
+							match output {
+								ReactionOutcome::DestroyReactants => {
+									commands.entity(entity_a).despawn();
+									spawn_particles(&mut commands, &mut rng, &textures, transform_a.translation.xy(), registry.get(m_info_a.kind).colour);
+									commands.entity(entity_b).despawn();
+									spawn_particles(&mut commands, &mut rng, &textures, transform_b.translation.xy(), registry.get(m_info_b.kind).colour);
+									if batted > 0.0 {
+										heat.add(-2.0 * CLEAR_COOLING);
 									}
-									_ => (),
+								}
+								ReactionOutcome::Molecule(kind) => {
+									spawn_molecule(&mut commands, &registry, pos, rand_vel(&mut rng), kind, batted);
+								}
+								ReactionOutcome::Bullet => {
+									spawn_bullet(&mut commands, &textures, pos, 6.0);
 								}
 							}
 						}
@@ -636,6 +564,10 @@ fn molecule_movement(
 			}
 
 			let relative_velocity = m_info_a.vel - m_info_b.vel;
+			let closing_speed = -relative_velocity.dot(offset) / offset.length();
+			if closing_speed > 0.0 {
+				heat.add_collision(closing_speed);
+			}
 			let dp = offset * relative_velocity.dot(offset) / (offset.length_squared() * (m_info_a.mass + m_info_b.mass));
 			m_info_a.vel -= 2.0 * m_info_b.mass * dp;
 			m_info_b.vel += 2.0 * m_info_a.mass * dp;
@@ -651,82 +583,234 @@ fn molecule_movement(
 		transform.scale = Vec2::splat(m_info.spawn_growth).extend(1.0);
 		m_info.reacted = false;
 		m_info.reaction_cooldown = (m_info.reaction_cooldown - time.delta_secs()).clamp(0.0, 10.0);
+		m_info.batted = (m_info.batted - time.delta_secs()).max(0.0);
 		transform.translation.x += m_info.vel.x * time.delta_secs();
 		transform.translation.y += m_info.vel.y * time.delta_secs();
 		let pos = transform.translation.xy();
-		if pos.x > 540.0 - 43.0/2.0 - m_info.radius || pos.x < -540.0 + 47.0/2.0 + m_info.radius {
+		let bounds = arena.bounds(m_info.radius);
+		if pos.x > bounds.max.x || pos.x < bounds.min.x {
 			m_info.vel.x = -m_info.vel.x;
 		}
-		if pos.y > 405.0 - 130.0/2.0 - m_info.radius || pos.y < -405.0 + 76.0 + m_info.radius {
+		if pos.y > bounds.max.y || pos.y < bounds.min.y {
 			m_info.vel.y = -m_info.vel.y;
 		}
 	}
 
-	let (mut p_info, p_transform) = player_query.single_mut().expect("Could not find player");
 	for (entity, _, m_transform) in molecule_query.iter_mut() {
 		let offset = p_transform.translation.xy() - m_transform.translation.xy();
 		if offset.length() <= p_info.radius + 8.0 {
-			take_damage(entity, &mut p_info, &mut commands, &textures, &audio, &sfx);
+			take_damage(entity, &mut p_info, &mut commands, &textures, &mut sfx, &mut shake);
 		}
 	}
 }
 
-fn clamp_inside_reactor(mut molecule_query: Query<(&MoleculeInfo, &mut Transform)>) {
-	for (m_info, mut transform) in molecule_query.iter_mut() {
-		let pos = transform.translation.xy();
-		if pos.x > 540.0 - 43.0/2.0 - m_info.radius {
-			transform.translation.x = 540.0 - 43.0/2.0 - m_info.radius;
+/// Parried bullets destroy the first molecule they hit, scoring as if it was cleared
+fn strike_with_parried_bullets(
+	mut commands: Commands,
+	mut player_query: Query<&mut PlayerInfo>,
+	bullet_query: Query<(Entity, &BulletInfo, &Transform)>,
+	molecule_query: Query<(Entity, &MoleculeInfo, &Transform), Without<BulletInfo>>,
+	textures: Res<TextureAssets>,
+	registry: Res<MoleculeRegistry>,
+	mut heat: ResMut<ReactorHeat>,
+	mut rng: ResMut<GameRng>,
+	mut sfx: EventWriter<PlaySfx>,
+) {
+	let mut p_info = player_query.single_mut().expect("Could not find player");
+	let mut struck = Vec::new();
+	for (b_entity, b_info, b_transform) in bullet_query.iter().filter(|(_, b_info, _)| b_info.parried()) {
+		let hit = molecule_query.iter().find(|(entity, m_info, m_transform)| {
+			!struck.contains(entity)
+				&& m_transform.translation.xy().distance(b_transform.translation.xy()) <= m_info.radius + b_info.radius
+		});
+		if let Some((m_entity, m_info, m_transform)) = hit {
+			struck.push(m_entity);
+			let info = registry.get(m_info.kind);
+			p_info.score += info.score;
+			heat.add(-CLEAR_COOLING);
+			commands.entity(m_entity).despawn();
+			commands.entity(b_entity).despawn();
+			spawn_particles(&mut commands, &mut rng, &textures, m_transform.translation.xy(), info.colour);
+			sfx.write(PlaySfx {sfx: Sfx::BounceAndCrackle, volume: 0.45, playback_rate: 0.5 + (rng.random::<f64>())});
 		}
-		if pos.x < -540.0 + 47.0/2.0 + m_info.radius {
-			transform.translation.x = -540.0 + 47.0/2.0 + m_info.radius;
+	}
+}
+
+/// Bounces molecules off the reactor and pushes bullets out of it, parried bullets are stopped by it
+fn collide_with_reactor(
+	mut commands: Commands,
+	reactor_query: Query<(&Reactor, &Transform)>,
+	mut molecule_query: Query<(&mut MoleculeInfo, &mut Transform), Without<Reactor>>,
+	mut bullet_query: Query<(Entity, &BulletInfo, &mut Transform), (Without<Reactor>, Without<MoleculeInfo>)>,
+	mut reactor_hits: EventWriter<ReactorHit>,
+	mut sfx: EventWriter<PlaySfx>,
+) {
+	let (reactor, r_transform) = reactor_query.single().expect("Could not find reactor");
+	let shape = Shape::Circle { radius: reactor.radius };
+	let centre = r_transform.translation.xy();
+	for (mut m_info, mut m_transform) in molecule_query.iter_mut() {
+		if let Some(push) = shape.push_out(m_transform.translation.xy() - centre, m_info.radius) {
+			m_transform.translation += push.extend(0.0);
+			let normal = push.normalize();
+			let into = m_info.vel.dot(normal);
+			if into < 0.0 {
+				m_info.vel -= 2.0 * into * normal;
+				reactor_hits.write(ReactorHit {kind: m_info.kind, speed: -into});
+				sfx.write(PlaySfx {sfx: Sfx::Ping, volume: 0.3, playback_rate: 0.5});
+			}
 		}
-		if pos.y > 405.0 - 130.0/2.0 - m_info.radius {
-			transform.translation.y = 405.0 - 130.0/2.0 - m_info.radius;
+	}
+	for (entity, b_info, mut b_transform) in bullet_query.iter_mut() {
+		if let Some(push) = shape.push_out(b_transform.translation.xy() - centre, b_info.radius) {
+			// Parried bullets fly straight, so they would only grind against it
+			if b_info.parried() {
+				commands.entity(entity).despawn();
+			}
+			b_transform.translation += push.extend(0.0);
 		}
-		if pos.y < -405.0 + 76.0 + m_info.radius {
-			transform.translation.y = -405.0 + 76.0 + m_info.radius;
+	}
+}
+
+fn glow_reactor(
+	mut reactor_hits: EventReader<ReactorHit>,
+	mut reactor_query: Query<(&mut ReactorGlow, &mut Sprite)>,
+	registry: Res<MoleculeRegistry>,
+	time: Res<Time>,
+) {
+	let Ok((mut glow, mut sprite)) = reactor_query.single_mut() else {
+		return;
+	};
+	for hit in reactor_hits.read() {
+		// Faster hits flash brighter
+		let amount = (hit.speed / REACTOR_GLOW_SPEED).min(1.0);
+		if amount >= glow.amount {
+			glow.colour = registry.get(hit.kind).colour;
+			glow.amount = amount;
 		}
 	}
+	glow.amount = (glow.amount - REACTOR_GLOW_DECAY * time.delta_secs()).max(0.0);
+	sprite.color = REACTOR_COLOUR.mix(&glow.colour, glow.amount);
+}
+
+fn clamp_inside_reactor(
+	mut molecule_query: Query<(&MoleculeInfo, &mut Transform)>,
+	arena: Res<Arena>,
+) {
+	for (m_info, mut transform) in molecule_query.iter_mut() {
+		let pos = arena.clamp(transform.translation.xy(), m_info.radius);
+		transform.translation = pos.extend(transform.translation.z);
+	}
 }
 
 fn destroy_molecules(
 	mut commands: Commands,
-	mut player_query: Query<&mut PlayerInfo>,
+	mut player_query: Query<(&mut PlayerInfo, &Transform)>,
 	textures: Res<TextureAssets>,
-	molecule_query: Query<(Entity, &MoleculeInfo, &Transform)>,
-	bullet_query: Query<(Entity, &BulletInfo, &Transform), Without<MoleculeInfo>>,
-	weapon_collider_query: Query<&GlobalTransform, With<WeaponCollider>>,
-	weapon_pivot_query: Query<(&Transform, &WeaponPivot)>,
-	audio: Res<Audio>,
-	sfx: Res<AudioAssets>,
+	registry: Res<MoleculeRegistry>,
+	mut molecule_query: Query<(Entity, &mut MoleculeInfo, &Transform), Without<PlayerInfo>>,
+	mut bullet_query: Query<(Entity, &mut BulletInfo, &mut Sprite, &mut Transform), (Without<MoleculeInfo>, Without<PlayerInfo>)>,
+	weapon_query: Query<(&WeaponPivot, &WeaponSweep)>,
+	mut heat: ResMut<ReactorHeat>,
+	mut rng: ResMut<GameRng>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut shake: EventWriter<CameraShake>,
 ) {
-	let mut p_info = player_query.single_mut().expect("Could not find player");
-	for (wp_transform, weapon) in weapon_pivot_query.iter(){
+	let (mut p_info, p_transform) = player_query.single_mut().expect("Could not find player");
+	for (weapon, sweep) in weapon_query.iter() {
 		if weapon.active {
-			for (entity, m_info, m_transform) in molecule_query.iter() {
-				for w_transform in weapon_collider_query.iter() {
-					let offset = m_transform.translation.xy() - w_transform.translation().xy();
-					if offset.length() <= m_info.radius + 6.0 * wp_transform.scale.x {
-						p_info.score += m_info.index as f32 + 1.0;
-						commands.entity(entity).despawn();
-						spawn_particles(&mut commands, &textures, m_transform.translation.xy(), m_info.index);
-						audio.play(sfx.bounce_and_crackle.clone()).with_volume(0.45).with_playback_rate(0.5 + (rand::random::<f64>()));
-						break;
+			for (entity, mut m_info, m_transform) in molecule_query.iter_mut() {
+				let pos = m_transform.translation.xy();
+				if !sweep.hits(pos, m_info.radius) {
+					continue;
+				}
+				if weapon.mode == WeaponMode::Bat {
+					// The molecule is carried along at least as fast as the part of the weapon that hit it,
+					// so staying in contact over several ticks doesn't keep speeding it up
+					let swing_velocity = weapon.swing_velocity(sweep.closest_point(pos) - p_transform.translation.xy());
+					let bat_speed = (swing_velocity.length() * weapon.weapon().knockback * weapon.charge_tier().knockback() * 2.0 * BAT_MASS / (BAT_MASS + m_info.mass)).min(MAX_BAT_SPEED);
+					let direction = swing_velocity.normalize_or_zero();
+					let along = m_info.vel.dot(direction);
+					if along < bat_speed {
+						m_info.vel += direction * (bat_speed - along);
+						m_info.batted = BATTED_DURATION;
+						sfx.write(PlaySfx {sfx: Sfx::Ping, volume: 0.5, playback_rate: 0.5 + (bat_speed / MAX_BAT_SPEED) as f64});
 					}
+					continue;
 				}
+				let info = registry.get(m_info.kind);
+				p_info.score += info.score;
+				heat.add(-CLEAR_COOLING);
+				commands.entity(entity).despawn();
+				spawn_particles(&mut commands, &mut rng, &textures, pos, info.colour);
+				sfx.write(PlaySfx {sfx: Sfx::BounceAndCrackle, volume: 0.45, playback_rate: 0.5 + (rng.random::<f64>())});
 			}
 
-			for (entity, b_info, m_transform) in bullet_query.iter() {
-				for w_transform in weapon_collider_query.iter() {
-					let offset = m_transform.translation.xy() - w_transform.translation().xy();
-					if offset.length() <= b_info.radius + 6.0 * wp_transform.scale.x {
-						p_info.score += 1.0;
-						spawn_particles(&mut commands, &textures, m_transform.translation.xy(), 5);
-						commands.entity(entity).despawn();
-						break;
-					}
+			for (entity, mut b_info, mut sprite, mut b_transform) in bullet_query.iter_mut() {
+				if b_info.parried() || !sweep.hits(b_transform.translation.xy(), b_info.radius) {
+					continue;
 				}
+				if weapon.parrying() {
+					// Sent back where it was fired from, or straight away from the player if it never left
+					let away = b_transform.translation.xy() - p_transform.translation.xy();
+					let direction = (b_info.origin - b_transform.translation.xy()).try_normalize()
+						.unwrap_or_else(|| away.normalize_or(Vec2::Y));
+					b_info.vel = direction * PARRIED_SPEED;
+					b_info.parried = Some(b_info.vel);
+					sprite.color = PARRIED_COLOUR;
+					b_transform.rotation = Quat::from_axis_angle(Vec3::Z, direction.to_angle() - 5.0*PI/4.0);
+					spawn_particles(&mut commands, &mut rng, &textures, b_transform.translation.xy(), PARRIED_COLOUR);
+					sfx.write(PlaySfx {sfx: Sfx::BatSwing, volume: 0.5, playback_rate: 2.0});
+					shake.write(CameraShake(0.2));
+					continue;
+				}
+				p_info.score += 1.0;
+				spawn_particles(&mut commands, &mut rng, &textures, b_transform.translation.xy(), BULLET_COLOUR);
+				commands.entity(entity).despawn();
 			}
 		}
 	}
+}
+
+fn spread_shockwaves(
+	mut commands: Commands,
+	mut player_query: Query<&mut PlayerInfo>,
+	mut shockwave_query: Query<(Entity, &mut Shockwave, &mut Sprite, &Transform)>,
+	molecule_query: Query<(Entity, &MoleculeInfo, &Transform), Without<Shockwave>>,
+	textures: Res<TextureAssets>,
+	registry: Res<MoleculeRegistry>,
+	mut heat: ResMut<ReactorHeat>,
+	mut rng: ResMut<GameRng>,
+	mut sfx: EventWriter<PlaySfx>,
+	time: Res<Time>,
+) {
+	let mut p_info = player_query.single_mut().expect("Could not find player");
+	let mut struck = Vec::new();
+	for (s_entity, mut shockwave, mut sprite, s_transform) in shockwave_query.iter_mut() {
+		let inner = SHOCKWAVE_RADIUS * shockwave.age / SHOCKWAVE_DURATION;
+		shockwave.age = (shockwave.age + time.delta_secs()).min(SHOCKWAVE_DURATION);
+		let outer = SHOCKWAVE_RADIUS * shockwave.age / SHOCKWAVE_DURATION;
+		sprite.custom_size = Some(Vec2::splat(outer * 2.0));
+		sprite.color = SHOCKWAVE_COLOUR.with_alpha(1.0 - shockwave.age / SHOCKWAVE_DURATION);
+
+		// Only what the ring crossed this tick, so molecules drifting in behind it are spared
+		let centre = s_transform.translation.xy();
+		for (m_entity, m_info, m_transform) in molecule_query.iter() {
+			let distance = m_transform.translation.xy().distance(centre);
+			if m_info.radius > SHOCKWAVE_MAX_MOLECULE_RADIUS || struck.contains(&m_entity)
+				|| distance - m_info.radius > outer || distance + m_info.radius < inner {
+				continue;
+			}
+			struck.push(m_entity);
+			let info = registry.get(m_info.kind);
+			p_info.score += info.score;
+			heat.add(-CLEAR_COOLING);
+			commands.entity(m_entity).despawn();
+			spawn_particles(&mut commands, &mut rng, &textures, m_transform.translation.xy(), info.colour);
+			sfx.write(PlaySfx {sfx: Sfx::BounceAndCrackle, volume: 0.45, playback_rate: 0.5 + (rng.random::<f64>())});
+		}
+
+		if shockwave.age >= SHOCKWAVE_DURATION {
+			commands.entity(s_entity).despawn();
+		}
+	}
 }
\ No newline at end of file
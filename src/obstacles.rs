@@ -0,0 +1,192 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::levels::Level;
+use crate::editor::PlayTest;
+use crate::loading::{DataAssets, TextureAssets};
+use crate::molecules::{BulletInfo, MoleculeInfo, SpawnTracker};
+use crate::player::{PlayerInfo, WALL_MARGIN};
+use crate::GameState;
+
+const OBSTACLE_COLOUR: Color = Color::linear_rgb(0.4, 0.64, 0.72);
+
+pub struct ObstaclesPlugin;
+
+/// This plugin places the obstacles of the level when a run starts and cleans them up afterwards
+/// Swapping obstacles between waves and colliding with them happens in the molecule systems, see [`update_wave_obstacles`]
+impl Plugin for ObstaclesPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(OnEnter(GameState::Playing), spawn_level_obstacles)
+			.add_systems(OnExit(GameState::Playing), cleanup_obstacles)
+			;
+	}
+}
+
+/// Outline of an obstacle, centred on its position
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum Shape {
+	Circle {
+		radius: f32,
+	},
+	Box {
+		size: Vec2,
+		/// Degrees clockwise
+		#[serde(default)]
+		angle: f32,
+	},
+}
+
+impl Shape {
+	/// How far a circle `offset` from the obstacle's centre has to move to stop overlapping it, if it overlaps at all
+	pub fn push_out(self, offset: Vec2, radius: f32) -> Option<Vec2> {
+		match self {
+			Shape::Circle { radius: obstacle_radius } => {
+				let distance = offset.length();
+				let reach = obstacle_radius + radius;
+				(distance < reach).then(|| offset.try_normalize().unwrap_or(Vec2::Y) * (reach - distance))
+			}
+			Shape::Box { size, angle } => {
+				let rotation = Vec2::from_angle(-angle.to_radians());
+				let local = Vec2::new(rotation.x, -rotation.y).rotate(offset);
+				let half_size = size / 2.0;
+				let closest = local.clamp(-half_size, half_size);
+				let push = if closest == local {
+					// Centre inside the box, so out through the nearest side
+					let depth = half_size - local.abs();
+					if depth.x < depth.y {
+						Vec2::new(local.x.signum() * (depth.x + radius), 0.0)
+					} else {
+						Vec2::new(0.0, local.y.signum() * (depth.y + radius))
+					}
+				} else {
+					let outside = local - closest;
+					let distance = outside.length();
+					if distance >= radius {
+						return None;
+					}
+					outside / distance * (radius - distance)
+				};
+				Some(rotation.rotate(push))
+			}
+		}
+	}
+}
+
+/// A static obstacle placed by a level
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ObstacleRecord {
+	pub position: Vec2,
+	pub shape: Shape,
+}
+
+#[derive(Component)]
+pub struct Obstacle(Shape);
+
+/// Only there during one wave
+#[derive(Component)]
+pub struct WaveObstacle;
+
+/// Wave whose obstacles are spawned
+#[derive(Resource)]
+pub struct ObstacleWave(usize);
+
+fn spawn_obstacle(commands: &mut Commands, textures: &TextureAssets, record: &ObstacleRecord) -> Entity {
+	let (image, size, angle) = match record.shape {
+		Shape::Circle { radius } => (textures.ball.clone(), Vec2::splat(radius * 2.0), 0.0),
+		Shape::Box { size, angle } => (textures.ditheredbackground.clone(), size, angle),
+	};
+	commands.spawn((
+		Sprite {
+			image,
+			color: OBSTACLE_COLOUR,
+			custom_size: Some(size),
+			..default()
+		},
+		Transform {
+			translation: record.position.extend(0.5),
+			rotation: Quat::from_rotation_z(-angle.to_radians()),
+			..default()
+		},
+		Obstacle(record.shape),
+	)).id()
+}
+
+fn spawn_level_obstacles(
+	mut commands: Commands,
+	textures: Res<TextureAssets>,
+	data: Res<DataAssets>,
+	play_test: Option<Res<PlayTest>>,
+	levels: Res<Assets<Level>>,
+) {
+	let level = levels.get(PlayTest::level(play_test.as_deref(), &data)).expect("Could not find level");
+	for record in level.obstacles.iter() {
+		spawn_obstacle(&mut commands, &textures, record);
+	}
+}
+
+/// Swaps in the obstacles of the current wave whenever it changes
+pub fn update_wave_obstacles(
+	mut commands: Commands,
+	spawn_tracker: Res<SpawnTracker>,
+	obstacle_wave: Option<Res<ObstacleWave>>,
+	wave_obstacle_query: Query<Entity, With<WaveObstacle>>,
+	levels: Res<Assets<Level>>,
+	textures: Res<TextureAssets>,
+) {
+	if obstacle_wave.is_some_and(|obstacle_wave| obstacle_wave.0 == spawn_tracker.wave()) {
+		return;
+	}
+	for entity in wave_obstacle_query.iter() {
+		commands.entity(entity).despawn();
+	}
+	let level = levels.get(spawn_tracker.level()).expect("Could not find level");
+	for record in level.waves[spawn_tracker.wave()].obstacles.iter() {
+		let entity = spawn_obstacle(&mut commands, &textures, record);
+		commands.entity(entity).insert(WaveObstacle);
+	}
+	commands.insert_resource(ObstacleWave(spawn_tracker.wave()));
+}
+
+/// Pushes molecules, bullets and the player out of obstacles, bouncing molecules and bullets off them
+pub fn collide_with_obstacles(
+	obstacle_query: Query<(&Obstacle, &Transform)>,
+	mut molecule_query: Query<(&mut MoleculeInfo, &mut Transform), Without<Obstacle>>,
+	mut bullet_query: Query<(&mut BulletInfo, &mut Transform), (Without<Obstacle>, Without<MoleculeInfo>)>,
+	mut player_query: Query<(&mut PlayerInfo, &mut Transform), (Without<Obstacle>, Without<MoleculeInfo>, Without<BulletInfo>)>,
+) {
+	for (Obstacle(shape), o_transform) in obstacle_query.iter() {
+		let centre = o_transform.translation.xy();
+		for (mut m_info, mut m_transform) in molecule_query.iter_mut() {
+			if let Some(push) = shape.push_out(m_transform.translation.xy() - centre, m_info.radius) {
+				m_transform.translation += push.extend(0.0);
+				let normal = push.normalize();
+				let into = m_info.vel.dot(normal);
+				if into < 0.0 {
+					m_info.vel -= 2.0 * into * normal;
+				}
+			}
+		}
+		for (mut b_info, mut b_transform) in bullet_query.iter_mut() {
+			if let Some(push) = shape.push_out(b_transform.translation.xy() - centre, b_info.radius) {
+				b_transform.translation += push.extend(0.0);
+				b_info.reflect(push.normalize());
+			}
+		}
+		for (mut p_info, mut p_transform) in player_query.iter_mut() {
+			if let Some(push) = shape.push_out(p_transform.translation.xy() - centre, WALL_MARGIN) {
+				p_transform.translation += push.extend(0.0);
+				// The player slides along the obstacle rather than bouncing
+				let normal = push.normalize();
+				let into = p_info.vel.dot(normal).min(0.0);
+				p_info.vel -= into * normal;
+			}
+		}
+	}
+}
+
+fn cleanup_obstacles(mut commands: Commands, obstacle_query: Query<Entity, With<Obstacle>>) {
+	for entity in obstacle_query.iter() {
+		commands.entity(entity).despawn();
+	}
+	commands.remove_resource::<ObstacleWave>();
+}
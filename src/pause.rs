@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use bevy::window::WindowFocused;
+
+use crate::actions::{Action, ActionState};
+use crate::player::PlayerInfo;
+use crate::ui::{button, button_label, ButtonColors};
+use crate::{GameState, PlayState};
+
+pub struct PausePlugin;
+
+/// This plugin pauses a run with the pause action, or when the window loses focus,
+/// and shows a menu to resume, restart or quit while paused
+impl Plugin for PausePlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(Update, toggle_pause.run_if(in_state(GameState::Playing)))
+			.add_systems(OnEnter(PlayState::Paused), setup_pause_menu)
+			.add_systems(Update, click_pause_button.run_if(in_state(PlayState::Paused)))
+			.add_systems(OnExit(PlayState::Paused), cleanup_pause_menu)
+			.add_systems(OnEnter(GameState::Restart), restart_run)
+			;
+	}
+}
+
+#[derive(Component)]
+struct PauseMenu;
+
+#[derive(Component, Clone, Copy)]
+enum PauseButton {
+	Resume,
+	Restart,
+	Quit,
+}
+
+fn toggle_pause(
+	mut next_play_state: ResMut<NextState<PlayState>>,
+	mut focus_events: EventReader<WindowFocused>,
+	play_state: Res<State<PlayState>>,
+	actions: Res<ActionState>,
+	player_query: Query<&PlayerInfo>,
+) {
+	// Once the player is dying the run is over, so there is nothing to pause
+	if player_query.iter().any(|p_info| p_info.death_countdown > 0.0) {
+		focus_events.clear();
+		return;
+	}
+	let pressed = actions.just_pressed(Action::Pause);
+	let lost_focus = focus_events.read().any(|event| !event.focused);
+	match play_state.get() {
+		PlayState::Running if pressed || lost_focus => next_play_state.set(PlayState::Paused),
+		PlayState::Paused if pressed => next_play_state.set(PlayState::Running),
+		_ => (),
+	}
+}
+
+fn setup_pause_menu(mut commands: Commands) {
+	commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			width: Val::Percent(100.0),
+			height: Val::Percent(100.0),
+			flex_direction: FlexDirection::Column,
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			row_gap: Val::Px(20.0),
+			..default()
+		},
+		BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.6)),
+		// Sits above the world and stops clicks reaching anything behind it
+		GlobalZIndex(10),
+		PauseMenu,
+	))
+	.with_children(|children| {
+		children.spawn((
+			Text::new("PAUSED"),
+			TextFont {
+				font_size: 50.0,
+				..default()
+			},
+			TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+		));
+		for (marker, label) in [
+			(PauseButton::Resume, "RESUME"),
+			(PauseButton::Restart, "RESTART"),
+			(PauseButton::Quit, "QUIT TO MENU"),
+		] {
+			children
+				.spawn(button(Node {width: Val::Px(260.0), height: Val::Px(50.0), ..default()}, marker))
+				.with_child(button_label(label, 30.0));
+		}
+	});
+}
+
+fn click_pause_button(
+	mut next_state: ResMut<NextState<GameState>>,
+	mut next_play_state: ResMut<NextState<PlayState>>,
+	mut interaction_query: Query<
+		(&Interaction, &mut BackgroundColor, &ButtonColors, &PauseButton),
+		(Changed<Interaction>, With<Button>),
+	>,
+) {
+	for (interaction, mut color, button_colors, button) in &mut interaction_query {
+		match *interaction {
+			Interaction::Pressed => match button {
+				PauseButton::Resume => next_play_state.set(PlayState::Running),
+				PauseButton::Restart => next_state.set(GameState::Restart),
+				PauseButton::Quit => next_state.set(GameState::Menu),
+			},
+			Interaction::Hovered => {
+				*color = button_colors.hovered.into();
+			}
+			Interaction::None => {
+				*color = button_colors.normal.into();
+			}
+		}
+	}
+}
+
+fn cleanup_pause_menu(mut commands: Commands, pause_menu: Query<Entity, With<PauseMenu>>) {
+	for entity in pause_menu.iter() {
+		commands.entity(entity).despawn();
+	}
+}
+
+fn restart_run(mut next_state: ResMut<NextState<GameState>>) {
+	next_state.set(GameState::Playing);
+}
@@ -1,16 +1,38 @@
-use std::f32::consts::PI;
+use std::f32::consts::{PI, TAU};
 use std::time::Duration;
 
-use bevy::{
-	prelude::*, 
-	window::PrimaryWindow, 
-	input::mouse::MouseButtonInput, 
-};
-use bevy_kira_audio::{Audio, AudioControl, AudioInstance, AudioTween};
-use crate::loading::{AudioAssets, TextureAssets};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use crate::arena::Arena;
+use crate::audio::{PlaySfx, Sfx};
+use crate::camera::CameraShake;
+use crate::highscores::HighScore;
+use crate::input::PlayerInput;
+use crate::leaderboard::FinishedRun;
+use crate::levels::Level;
+use crate::editor::PlayTest;
+use crate::loading::{DataAssets, TextureAssets};
 use crate::menu::DeathFadeout;
-use crate::molecules::{BulletInfo, Crosses, MoleculeInfo, Reactor, Score};
-use crate::GameState;
+use crate::molecules::{spawn_shockwave, BulletInfo, Crosses, MoleculeInfo, Reactor, Shockwave};
+use crate::replay::ReplayPlayback;
+use crate::weapons::{choose_starting_weapon, pick_up_weapons, Blade, Loadout, WeaponDefinition, WeaponSet};
+use crate::simulation::{GameRng, SimulationSet};
+use crate::{GameState, PlayState};
+
+/// Seconds from the start of a swing during which bullets are parried rather than destroyed
+const PARRY_WINDOW: f32 = 0.1;
+/// Share of the weapon's windup a swing has to be charged to before release to be a medium or full swing
+const MEDIUM_CHARGE: f32 = 0.5;
+const FULL_CHARGE: f32 = 0.9;
+/// Seconds the player is slowed for after a fully charged swing
+const FULL_CHARGE_SLOWDOWN: f32 = 0.8;
+/// Share of their top speed the player keeps while slowed
+const SLOWED_SPEED: f32 = 0.4;
+/// Tint of the weapon as it charges, reaching this colour at a full charge
+const CHARGED_COLOUR: Color = Color::linear_rgb(1.0, 0.55, 0.15);
+
+/// How close the player's centre gets to a wall or obstacle, the edges of the sprite are see-through
+pub const WALL_MARGIN: f32 = 12.0;
 
 #[derive(Component)]
 pub struct PlayerInfo {
@@ -24,37 +46,192 @@ pub struct PlayerInfo {
 	pub radius: f32,
 	pub stun_duration: f32,
 	pub invul_duration: f32,
+	/// Seconds left moving slower after a fully charged swing
+	pub slow_duration: f32,
 }
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
 	fn build(&self, app: &mut App) {
-		app.add_systems(OnEnter(GameState::Playing), spawn_player)
-			.add_systems(Update, (
+		app.add_systems(OnEnter(GameState::Playing), spawn_player.after(choose_starting_weapon))
+			.add_systems(FixedUpdate, (
+				pick_up_weapons,
 				weapon_swing,
 				player_movement,
-				execute_animations,
+				sweep_weapon,
 				check_player_lives,
-			).chain().run_if(in_state(GameState::Playing)))
+			).chain().in_set(SimulationSet::Player))
+			.add_systems(Update, (execute_animations, show_charge).run_if(in_state(PlayState::Running)))
 			.add_systems(OnExit(GameState::Playing), cleanup_game)
 		;
 	}
 }
 
+/// What the weapon does to the molecules it touches
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, Debug)]
+pub enum WeaponMode {
+	/// Clears them
+	#[default]
+	Erase,
+	/// Knocks them along the swing, scoring for the reactions they cause
+	Bat,
+}
+
+/// How far a swing was charged before it was released, stronger swings reach further and knock harder
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug)]
+pub enum ChargeTier {
+	Tap,
+	Medium,
+	/// Also sends out a shockwave and slows the player for a moment
+	Full,
+}
+
+impl ChargeTier {
+	fn from_charge(charge: f32) -> Self {
+		if charge >= FULL_CHARGE {ChargeTier::Full}
+		else if charge >= MEDIUM_CHARGE {ChargeTier::Medium}
+		else {ChargeTier::Tap}
+	}
+
+	/// Multiplier on the size of the swinging weapon
+	pub fn reach(self) -> f32 {
+		match self {
+			ChargeTier::Tap => 1.0,
+			ChargeTier::Medium => 1.15,
+			ChargeTier::Full => 1.35,
+		}
+	}
+
+	/// Multiplier on the weapon's knockback
+	pub fn knockback(self) -> f32 {
+		match self {
+			ChargeTier::Tap => 1.0,
+			ChargeTier::Medium => 1.3,
+			ChargeTier::Full => 1.8,
+		}
+	}
+}
+
 #[derive(Component)]
 pub struct WeaponPivot {
+	pub mode: WeaponMode,
+	weapon: WeaponDefinition,
 	time_left: f32,
-	max_time: f32,
 	backswing: f32,
 	held: bool,
+	/// Swing button state on the previous tick, to spot presses and releases
+	button_held: bool,
 	swinging: bool,
 	pub active: bool,
 	clockwise_swing: bool,
 }
 
+impl WeaponPivot {
+	/// Velocity of the point of the swinging weapon `offset` from the player, faster the further the backswing was charged
+	pub fn swing_velocity(&self, offset: Vec2) -> Vec2 {
+		let angular_speed = (self.weapon.arc.to_radians() + self.backswing) / self.weapon.swing_time * (1.0 + 2.0 * self.backswing);
+		let tangent = if self.clockwise_swing {-offset.perp()} else {offset.perp()};
+		tangent * angular_speed
+	}
+
+	/// Whether the swing is early enough to parry bullets back
+	pub fn parrying(&self) -> bool {
+		self.active && self.weapon.swing_time - self.time_left <= PARRY_WINDOW
+	}
+
+	/// How much of the weapon's windup has been charged, from `0.0` to `1.0`
+	pub fn charge(&self) -> f32 {
+		if self.weapon.windup.max > 0.0 {self.backswing / self.weapon.windup.max} else {0.0}
+	}
+
+	/// Tier of the swing being charged or under way
+	pub fn charge_tier(&self) -> ChargeTier {
+		ChargeTier::from_charge(self.charge())
+	}
+
+	pub fn weapon(&self) -> &WeaponDefinition {
+		&self.weapon
+	}
+
+	/// Whether a swing is being wound up or is under way
+	pub fn swinging(&self) -> bool {
+		self.swinging
+	}
+
+	pub fn equip(&mut self, weapon: WeaponDefinition) {
+		self.weapon = weapon;
+	}
+}
+
+/// The weapon's sprite, a child of the [`WeaponPivot`]
 #[derive(Component)]
-pub struct WeaponCollider;
+pub struct WeaponSprite;
+
+/// Where the blade of the weapon went over the last tick, so a fast swing can't skip past anything
+/// Worked out from the simulation's own transforms, which unlike `GlobalTransform` are never a frame behind
+#[derive(Component)]
+pub struct WeaponSweep {
+	pivot: Vec2,
+	scale: f32,
+	blade: Blade,
+	/// Rotation of the weapon in the world a tick ago and now
+	from: f32,
+	to: f32,
+}
+
+impl WeaponSweep {
+	fn blade(&self, angle: f32) -> (Vec2, Vec2) {
+		let rotation = Vec2::from_angle(angle);
+		(self.pivot + rotation.rotate(self.blade.start * self.scale), self.pivot + rotation.rotate(self.blade.end * self.scale))
+	}
+
+	/// Closest point of the blade as it is now
+	pub fn closest_point(&self, point: Vec2) -> Vec2 {
+		let (start, end) = self.blade(self.to);
+		let along = (point - start).dot(end - start) / (end - start).length_squared();
+		start + (end - start) * along.clamp(0.0, 1.0)
+	}
+
+	/// Whether the blade touched a circle at `point` at any moment over the last tick
+	pub fn hits(&self, point: Vec2, radius: f32) -> bool {
+		let reach = radius + self.blade.radius * self.scale;
+		for angle in [self.from, self.to] {
+			let (start, end) = self.blade(angle);
+			let along = ((point - start).dot(end - start) / (end - start).length_squared()).clamp(0.0, 1.0);
+			if point.distance(start + (end - start) * along) <= reach {
+				return true;
+			}
+		}
+
+		// In between, the blade passes over the point's centre if it is in reach of the pivot at all
+		let offset = point - self.pivot;
+		let (start, direction) = (self.blade.start * self.scale, (self.blade.end - self.blade.start) * self.scale);
+		let closest = start + direction * (-start.dot(direction) / direction.length_squared()).clamp(0.0, 1.0);
+		let (near, far) = (closest.length(), start.length().max((start + direction).length()));
+		let distance = offset.length();
+		if distance < near - reach || distance > far + reach {
+			return false;
+		}
+		// The point along the blade, going away from the player, as far from the pivot as the circle is
+		let along = distance.clamp(near, far);
+		let (a, b, c) = (direction.length_squared(), start.dot(direction), start.length_squared() - along * along);
+		let t = ((-b + (b * b - a * c).max(0.0).sqrt()) / a).clamp(0.0, 1.0);
+		let crossing = offset.to_angle() - (start + direction * t).to_angle();
+		let swept = wrap_angle(self.to - self.from);
+		let turned = wrap_angle(crossing - self.from);
+		if swept >= 0.0 {
+			(0.0..=swept).contains(&turned)
+		} else {
+			(swept..=0.0).contains(&turned)
+		}
+	}
+}
+
+/// The same angle between -PI and PI
+fn wrap_angle(angle: f32) -> f32 {
+	(angle + PI).rem_euclid(TAU) - PI
+}
 
 #[derive(Component)]
 struct AnimationConfig {
@@ -108,8 +285,23 @@ fn execute_animations(
 fn spawn_player(
 	mut commands: Commands,
 	textures: Res<TextureAssets>,
+	data: Res<DataAssets>,
+	play_test: Option<Res<PlayTest>>,
+	levels: Res<Assets<Level>>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+	loadout: Res<Loadout>,
+	asset_server: Res<AssetServer>,
 	mut texture_atlas_layouts: ResMut<Assets<TextureAtlasLayout>>,
 ) {
+	let level = levels.get(PlayTest::level(play_test.as_deref(), &data)).expect("Could not find level");
+	let mode = level.weapon_mode;
+	let weapon = weapon_sets.get(&data.weapons).expect("Could not find weapons").get_or_default(&loadout.current).clone();
+	let weapon_sprite = Sprite {
+		image: asset_server.load(&weapon.sprite),
+		custom_size: Some(weapon.sprite_size),
+		..default()
+	};
+	let weapon_offset = weapon.sprite_offset.extend(0.0);
 	let layout = TextureAtlasLayout::from_grid(UVec2::splat(48), 12, 1, None, None);
     let texture_atlas_layout = texture_atlas_layouts.add(layout);
 	let animation_config = AnimationConfig::new(0, 11, 16);
@@ -138,96 +330,86 @@ fn spawn_player(
 			radius,
 			stun_duration: 0.0,
 			invul_duration: 0.0,
+			slow_duration: 0.0,
 		},
 	)).with_children(move |player| {
 		player.spawn((
 			Transform::default(),
 			Visibility::Visible,
+			WeaponSweep {
+				pivot: Vec2::ZERO,
+				scale: 1.0,
+				blade: weapon.blade,
+				from: 0.0,
+				to: 0.0,
+			},
 			WeaponPivot {
+				mode,
+				weapon,
 				time_left: 0.0,
-				max_time: 0.3,
 				backswing: 0.0,
 				held: false,
+				button_held: false,
 				swinging: false,
 				active: false,
 				clockwise_swing: true,
 			},
-		)).with_children(|weapon_pivot| {
-			weapon_pivot.spawn((
-				Sprite {
-					image: textures.weapon.clone(),
-					custom_size: Some(Vec2::new(24.0, 96.0)),
-					..default()
-				},
-				Transform {
-					translation: Vec3::new(8.0, 72.0, 0.0),
-					..Default::default()
-				},
-			));
-			for i in 0..12 {
-				weapon_pivot.spawn((
-				Sprite {
-					image: textures.hoop.clone(),
-					custom_size: Some(Vec2::new(12.0, 12.0)),
-					..default()
-				},
-				Transform {
-					translation: Vec3::new(8.0, radius + 8.0 * i as f32, 0.0),
-					..Default::default()
-				},
-				Visibility::Hidden,
-				WeaponCollider,
-			));
-			}
-		});
+		)).with_child((
+			weapon_sprite,
+			Transform::from_translation(weapon_offset),
+			WeaponSprite,
+		));
 	});
 }
 
 fn player_movement(
 	mut player_query: Query<(&mut PlayerInfo, &mut Transform)>,
-	reactor_query: Query<&Transform, (With<Reactor>, Without<PlayerInfo>)>,
+	reactor_query: Query<(&Reactor, &Transform), Without<PlayerInfo>>,
 	weapon_pivot_query: Query<&WeaponPivot>,
-	windows: Query<&Window, With<PrimaryWindow>>,
+	input: Res<PlayerInput>,
+	arena: Res<Arena>,
 	time: Res<Time>,
 ) {
 	let (mut player, mut transform) = player_query.single_mut().expect("Could not find player");
-	let window = windows.single().expect("Could not find window");
-	let window_size = Vec2::new(window.width(), window.height());
 	if player.invul_duration > 0.0 {
 		player.invul_duration = (player.invul_duration - time.delta_secs()).clamp(0.0, 10.0);
 	}
+	let max_vel = if player.slow_duration > 0.0 {player.max_vel * SLOWED_SPEED} else {player.max_vel};
+	player.slow_duration = (player.slow_duration - time.delta_secs()).max(0.0);
 	for weapon_pivot in weapon_pivot_query.iter() {
 		if player.stun_duration == 0.0 {
-			if let Some(mut target) = window.cursor_position() {
-				target -= window_size / 2.0;
-				target.y = -target.y;
-				let offset = target - transform.translation.xy();
-
-				if !weapon_pivot.swinging && offset.length() >= 10.0 {
-					let direction = offset.normalize();
-					player.vel = (player.vel + player.acc * direction * time.delta_secs()).clamp_length_max(player.max_vel);
-				} else {
-					player.vel = Vec2::ZERO;
+			// The mouse steers toward the cursor, keys and sticks steer in a direction
+			let (movement, facing) = match input.target {
+				Some(target) => {
+					let offset = target - transform.translation.xy();
+					let movement = if offset.length() >= 10.0 {offset.normalize()} else {Vec2::ZERO};
+					(movement, Some(offset))
 				}
+				None => (input.movement, input.aim.or((input.movement != Vec2::ZERO).then_some(input.movement))),
+			};
 
-				if !weapon_pivot.active {
-					let angle = offset.y.atan2(offset.x);
-					transform.rotation = Quat::from_rotation_z(angle);
-				}
-				
-				let mut move_target: Vec2 = Vec2::ZERO;
-				move_target.x = (transform.translation.x + player.vel.x * time.delta_secs()).clamp(-540.0 + 47.0 + 12.0, 540.0 - 43.0 - 12.0);
-				move_target.y = (transform.translation.y + player.vel.y * time.delta_secs()).clamp(-405.0 + 76.0 + 12.0, 405.0 - 130.0 - 12.0);
+			if !weapon_pivot.swinging && movement != Vec2::ZERO {
+				// A stick pushed part of the way moves the player slower
+				player.vel = (player.vel + player.acc * movement * time.delta_secs()).clamp_length_max(max_vel * movement.length());
+			} else {
+				player.vel = Vec2::ZERO;
+			}
 
-				let reactor_loc = reactor_query.single().expect("Could not find reactor");
+			if let Some(facing) = facing.filter(|_| !weapon_pivot.active) {
+				let angle = facing.y.atan2(facing.x);
+				transform.rotation = Quat::from_rotation_z(angle);
+			}
+			
+			let move_target = arena.clamp(transform.translation.xy() + player.vel * time.delta_secs(), WALL_MARGIN);
 
-				if ((move_target - reactor_loc.translation.xy()).length()) > 24.0 + 64.0 {
-					transform.translation = move_target.extend(100.0);
-				} else {
-					transform.translation = ((move_target - reactor_loc.translation.xy()).normalize() * (24.0 + 64.0)).extend(100.0);
-				}
+			let (reactor, r_transform) = reactor_query.single().expect("Could not find reactor");
+			let centre = r_transform.translation.xy();
+			let reach = reactor.radius + player.radius;
+
+			if (move_target - centre).length() > reach {
+				transform.translation = move_target.extend(100.0);
 			} else {
-				player.vel = Vec2::ZERO;
+				transform.translation = (centre + (move_target - centre).normalize_or(Vec2::Y) * reach).extend(100.0);
 			}
 		} else {
 			player.stun_duration = (player.stun_duration - time.delta_secs()).clamp(0.0, 10.0);
@@ -236,17 +418,40 @@ fn player_movement(
 	}
 }
 
+fn sweep_weapon(
+	player_query: Query<&Transform, With<PlayerInfo>>,
+	mut weapon_query: Query<(&WeaponPivot, &Transform, &mut WeaponSweep), Without<PlayerInfo>>,
+) {
+	let p_transform = player_query.single().expect("Could not find player");
+	for (weapon_pivot, w_transform, mut sweep) in weapon_query.iter_mut() {
+		let world = p_transform.mul_transform(*w_transform);
+		let angle = (world.rotation * Vec3::X).xy().to_angle();
+		// Includes the jump from the wound up pose on the tick the swing is released
+		sweep.from = sweep.to;
+		sweep.to = angle;
+		sweep.pivot = world.translation.xy();
+		sweep.scale = world.scale.x;
+		sweep.blade = weapon_pivot.weapon.blade;
+	}
+}
+
 fn check_player_lives(
+	mut commands: Commands,
 	mut next_state: ResMut<NextState<GameState>>,
 	mut player_query: Query<&mut PlayerInfo>,
 	mut death_query: Query<&mut Sprite, With<DeathFadeout>>,
-	mut score_query: Query<&mut Score>,
+	mut highscore: ResMut<HighScore>,
+	playback: Option<Res<ReplayPlayback>>,
+	play_test: Option<Res<PlayTest>>,
+	loadout: Res<Loadout>,
 	time: Res<Time>,
 ) {
 	let mut p_info = player_query.single_mut().expect("Could not find player");
-	let mut sprite = death_query.single_mut().expect("Could not find death fadeout");
 	if p_info.death_countdown > 0.0 {
-		sprite.color = Color::linear_rgba(0.0, 0.0, 0.0, 1.0 - p_info.death_countdown/1.5);
+		// The fadeout is only there when the game is drawn
+		if let Ok(mut sprite) = death_query.single_mut() {
+			sprite.color = Color::linear_rgba(0.0, 0.0, 0.0, 1.0 - p_info.death_countdown/1.5);
+		}
 		if p_info.invul_duration == 0.0 {p_info.invul_duration = 1.0};
 		p_info.death_countdown = (p_info.death_countdown - time.delta_secs()).clamp(0.0, 10.0);
 		if p_info.death_countdown == 0.0 {
@@ -255,9 +460,16 @@ fn check_player_lives(
 	} else if p_info.lives <= 0.0 {
 		// println!("Score: {}", p_info.score);
 		// println!("Time Survived: {}", p_info.time_survived);
-		let mut score = score_query.single_mut().expect("Could not find score");
-		if p_info.score > score.highscore {score.highscore = p_info.score};
-		if p_info.time_survived > score.hightime {score.hightime = p_info.time_survived}; 
+		// Watching a replay or trying out the editor's level doesn't count as a new run
+		if playback.is_none() && play_test.is_none() {
+			if p_info.score > highscore.score {highscore.score = p_info.score};
+			if p_info.time_survived > highscore.time {highscore.time = p_info.time_survived}; 
+			commands.insert_resource(FinishedRun {
+				score: p_info.score,
+				time: p_info.time_survived,
+				weapon: loadout.current.clone(),
+			});
+		}
 		p_info.death_countdown = 1.5;
 	} else {
 		p_info.time_survived += time.delta_secs();
@@ -265,61 +477,69 @@ fn check_player_lives(
 }
 
 fn weapon_swing(
-	mut mouse_events: EventReader<MouseButtonInput>,
-	mut weapon_query: Query<(&mut WeaponPivot, &mut Transform)>,
-	mut audio_instances: ResMut<Assets<AudioInstance>>,
-	mut wind_handle: Local<Handle<AudioInstance>>,
-	player_query: Query<&PlayerInfo>,
+	mut commands: Commands,
+	mut weapon_query: Query<(&mut WeaponPivot, &mut Transform), Without<PlayerInfo>>,
+	mut rng: ResMut<GameRng>,
+	mut player_query: Query<(&mut PlayerInfo, &Transform)>,
+	textures: Res<TextureAssets>,
+	input: Res<PlayerInput>,
 	time: Res<Time>,
-	audio: Res<Audio>,
-	sfx: Res<AudioAssets>,
+	mut sfx: EventWriter<PlaySfx>,
+	mut shake: EventWriter<CameraShake>,
 ) {
-	for event in mouse_events.read() {
-		if event.button == MouseButton::Left && event.state.is_pressed() {
-			let player = player_query.single().expect("Player not found");
-			if player.stun_duration == 0.0 {
-				for (mut weapon_pivot, _) in weapon_query.iter_mut() {
-					if !weapon_pivot.swinging {
-						weapon_pivot.time_left = weapon_pivot.max_time;
-						weapon_pivot.held = true;
-						weapon_pivot.swinging = true;
-						*wind_handle = audio.play(sfx.wind_up.clone()).with_volume(0.2).with_playback_rate(0.875 + rand::random::<f64>()/4.0).handle();
-					}
-				}
+	for (mut weapon_pivot, _) in weapon_query.iter_mut() {
+		let (pressed, released) = (input.swing && !weapon_pivot.button_held, !input.swing && weapon_pivot.button_held);
+		weapon_pivot.button_held = input.swing;
+		let (mut player, p_transform) = player_query.single_mut().expect("Player not found");
+		if pressed {
+			if player.stun_duration == 0.0 && !weapon_pivot.swinging {
+				weapon_pivot.time_left = weapon_pivot.weapon.swing_time;
+				weapon_pivot.held = true;
+				weapon_pivot.swinging = true;
+				sfx.write(PlaySfx {sfx: Sfx::WindUp, volume: 0.2, playback_rate: 0.875 + rng.random::<f64>()/4.0});
 			}
-		} else {
-			for (mut weapon_pivot, _) in weapon_query.iter_mut() {
-				if weapon_pivot.swinging && !weapon_pivot.active {
-					weapon_pivot.held = false;
-					weapon_pivot.active = true;
-					if let Some(instance) = audio_instances.get_mut(&**&mut wind_handle) {
-						instance.pause(AudioTween::default());
-					}
-					audio.play(sfx.bat_swing.clone()).with_volume(0.25).with_playback_rate(0.5 + rand::random::<f64>());
-				}
+		} else if released && weapon_pivot.swinging && !weapon_pivot.active {
+			weapon_pivot.held = false;
+			weapon_pivot.active = true;
+			if weapon_pivot.charge_tier() == ChargeTier::Full {
+				// Paid for with a moment of slow movement once the swing is over
+				player.slow_duration = weapon_pivot.weapon.swing_time + FULL_CHARGE_SLOWDOWN;
+				spawn_shockwave(&mut commands, &textures, p_transform.translation.xy());
+				sfx.write(PlaySfx {sfx: Sfx::BatSwing, volume: 0.4, playback_rate: 0.4});
+				shake.write(CameraShake(0.4));
+			} else {
+				sfx.write(PlaySfx {sfx: Sfx::BatSwing, volume: 0.25, playback_rate: 0.5 + rng.random::<f64>()});
 			}
 		}
 	}
 
-	let backswing_amount = PI/2.0;
-	let total_angle = PI;
 	let offset_angle = 0.0;
 
 	for (mut weapon_pivot, mut transform) in weapon_query.iter_mut() {
+		let windup = weapon_pivot.weapon.windup;
+		let total_angle = weapon_pivot.weapon.arc.to_radians();
 		if weapon_pivot.held {
-			weapon_pivot.backswing = (weapon_pivot.backswing + (0.5 - weapon_pivot.backswing) * time.delta_secs() * 4.0).clamp(0.0, 0.5);
-			let backswing_angle = if weapon_pivot.clockwise_swing {weapon_pivot.backswing * backswing_amount}
-				else {-weapon_pivot.backswing * backswing_amount + total_angle};
+			let tier = weapon_pivot.charge_tier();
+			weapon_pivot.backswing = (weapon_pivot.backswing + (windup.max - weapon_pivot.backswing) * time.delta_secs() * windup.rate).clamp(0.0, windup.max);
+			if weapon_pivot.charge_tier() > tier {
+				// A higher ping for each tier reached
+				let playback_rate = if weapon_pivot.charge_tier() == ChargeTier::Full {2.0} else {1.5};
+				sfx.write(PlaySfx {sfx: Sfx::Ping, volume: 0.3, playback_rate});
+			}
+			// Drawn back in proportion to the charge, reaching the weapon's full windup angle at its most
+			let drawn_back = if windup.max > 0.0 {weapon_pivot.backswing / windup.max * windup.angle.to_radians()} else {0.0};
+			let backswing_angle = if weapon_pivot.clockwise_swing {drawn_back}
+				else {-drawn_back + total_angle};
 			transform.rotation = Quat::from_rotation_z(backswing_angle);
 		} else if !weapon_pivot.held && weapon_pivot.swinging {
 			weapon_pivot.time_left = (weapon_pivot.time_left - time.delta_secs()).clamp(0.0, 10.0);
-			let percent = (weapon_pivot.time_left / weapon_pivot.max_time).clamp(0.0, 1.0);
+			let percent = (weapon_pivot.time_left / weapon_pivot.weapon.swing_time).clamp(0.0, 1.0);
 			let angle = if weapon_pivot.clockwise_swing {((1.0 - percent) * (total_angle + weapon_pivot.backswing)) - (weapon_pivot.backswing + offset_angle)}
 				else {percent * (total_angle + weapon_pivot.backswing) - (weapon_pivot.backswing + offset_angle)};
 			transform.rotation = Quat::from_rotation_z(-angle);
 
 			let scale = (1.0 + ((1.0 - percent).powf(0.7) * PI).sin()) * (0.65 + weapon_pivot.backswing/4.0) ;
-			transform.scale = Vec2::splat((scale).clamp(1.0, 2.0)).extend(0.0);
+			transform.scale = Vec2::splat(scale.clamp(1.0, 2.0) * weapon_pivot.charge_tier().reach()).extend(0.0);
 
 			if weapon_pivot.swinging && weapon_pivot.time_left == 0.0 {
 				transform.rotation = if weapon_pivot.clockwise_swing{Quat::from_rotation_z(total_angle-offset_angle)} else {Quat::from_rotation_z(offset_angle)};
@@ -332,6 +552,22 @@ fn weapon_swing(
 	}
 }
 
+/// Tints the weapon as its swing charges, glowing once it reaches a full charge
+fn show_charge(
+	weapon_query: Query<&WeaponPivot>,
+	mut sprite_query: Query<&mut Sprite, With<WeaponSprite>>,
+	time: Res<Time>,
+) {
+	let Ok(weapon_pivot) = weapon_query.single() else {return};
+	let colour = match weapon_pivot.charge_tier() {
+		ChargeTier::Full => CHARGED_COLOUR.mix(&Color::WHITE, 0.25 + 0.25 * (time.elapsed_secs() * 20.0).sin()),
+		_ => Color::WHITE.mix(&CHARGED_COLOUR, weapon_pivot.charge() / FULL_CHARGE * 0.6),
+	};
+	for mut sprite in sprite_query.iter_mut() {
+		sprite.color = colour;
+	}
+}
+
 fn cleanup_game(
 	mut commands: Commands,
 	player_query: Query<Entity, With<PlayerInfo>>,
@@ -339,6 +575,7 @@ fn cleanup_game(
 	bullet_query: Query<Entity, (Without<PlayerInfo>, Without<MoleculeInfo>, With<BulletInfo>)>,
 	reactor_query: Query<Entity, (Without<PlayerInfo>, Without<MoleculeInfo>, Without<BulletInfo>, With<Reactor>)>,
 	crosses_query: Query<Entity, (Without<PlayerInfo>, Without<MoleculeInfo>, Without<BulletInfo>, Without<Reactor>, With<Crosses>)>,
+	shockwave_query: Query<Entity, With<Shockwave>>,
 ) {
 	let p_entity = player_query.single().expect("Could not find player");
 	commands.entity(p_entity).despawn();
@@ -354,4 +591,51 @@ fn cleanup_game(
 	for entity in crosses_query.iter() {
 		commands.entity(entity).despawn();
 	}
-}
\ No newline at end of file
+	for entity in shockwave_query.iter() {
+		commands.entity(entity).despawn();
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const BLADE: Blade = Blade {start: Vec2::new(8.0, 24.0), end: Vec2::new(8.0, 112.0), radius: 6.0};
+
+	fn sweep(from: f32, to: f32) -> WeaponSweep {
+		WeaponSweep {pivot: Vec2::ZERO, scale: 1.0, blade: BLADE, from, to}
+	}
+
+	/// Which of `points` a swing from `start` to `end` split evenly into `ticks` touches
+	fn swing_hits(start: f32, end: f32, ticks: usize, points: &[Vec2]) -> Vec<bool> {
+		let angle = |tick: usize| start + (end - start) * tick as f32 / ticks as f32;
+		points.iter().map(|&point| (1..=ticks).any(|tick| sweep(angle(tick - 1), angle(tick)).hits(point, 10.0))).collect()
+	}
+
+	#[test]
+	fn frame_rate_does_not_change_what_a_swing_hits() {
+		let points: Vec<Vec2> = (-25..=25).flat_map(|x| (-25..=25).map(move |y| Vec2::new(x as f32, y as f32) * 6.0)).collect();
+		// A 0.3 second swing through 180 degrees at 30 and 144 frames a second
+		let (slow, fast) = (swing_hits(0.0, -PI, 9, &points), swing_hits(0.0, -PI, 43, &points));
+		let differing: Vec<Vec2> = points.iter().zip(slow.iter().zip(fast.iter()))
+			.filter(|(_, (slow, fast))| slow != fast)
+			.map(|(&point, _)| point)
+			.collect();
+		assert!(differing.is_empty(), "hit at only one frame rate: {differing:?}");
+		assert!(slow.iter().any(|&hit| hit));
+	}
+
+	#[test]
+	fn one_tick_covers_the_whole_arc() {
+		// Halfway round a swing that turns 2.5 radians in a single tick, nowhere near either end
+		let point = Vec2::from_angle(-1.25).rotate(Vec2::new(8.0, 70.0));
+		assert!(sweep(0.0, -2.5).hits(point, 4.0));
+		assert!(!sweep(0.0, -0.5).hits(point, 4.0));
+	}
+
+	#[test]
+	fn misses_what_the_blade_never_reaches() {
+		assert!(!sweep(0.0, -PI / 2.0).hits(Vec2::new(0.0, 200.0), 10.0));
+		assert!(!sweep(0.0, -PI / 2.0).hits(Vec2::new(-100.0, 0.0), 10.0));
+	}
+}
@@ -188,5 +188,7 @@ pub struct PostProcessSettings {
     pub scanline_freq: f32,
     pub line_intensity: f32,
     pub _padding: f32,
+    /// Where the camera's viewport sits in the window, as offset and size in UV coordinates
+    pub viewport: Vec4,
     pub _webgl2_padding: Vec3,
 }
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::Deserialize;
+use thiserror::Error;
+
+use crate::registry::MoleculeKind;
+
+pub struct ReactionsPlugin;
+
+/// Registers the reaction table asset so it can be loaded by [`crate::loading::LoadingPlugin`]
+impl Plugin for ReactionsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_asset::<ReactionTable>()
+			.init_asset_loader::<ReactionTableLoader>();
+	}
+}
+
+pub enum ReactionInfo<'a> {
+	Reaction(&'a [ReactionOutcome]),
+	None,
+}
+
+/// Something that happens when two molecules react
+#[derive(Deserialize, Clone, Copy, PartialEq, Debug)]
+pub enum ReactionOutcome {
+	/// Both reactants are destroyed
+	DestroyReactants,
+	/// A molecule of this kind is released
+	Molecule(MoleculeKind),
+	/// A bullet is fired at the player
+	Bullet,
+}
+
+/// Every reaction between two molecule types, keyed by the sorted pair of kinds
+#[derive(Asset, TypePath, Debug)]
+pub struct ReactionTable {
+	reactions: HashMap<(MoleculeKind, MoleculeKind), Vec<ReactionOutcome>>,
+}
+
+impl ReactionTable {
+	pub fn get(&self, a: MoleculeKind, b: MoleculeKind) -> ReactionInfo<'_> {
+		match self.reactions.get(&(a.min(b), a.max(b))) {
+			Some(products) => ReactionInfo::Reaction(products),
+			None => ReactionInfo::None,
+		}
+	}
+}
+
+#[derive(Deserialize)]
+struct ReactionTableFile {
+	reactions: Vec<ReactionEntry>,
+}
+
+#[derive(Deserialize)]
+struct ReactionEntry {
+	reactants: (MoleculeKind, MoleculeKind),
+	products: Vec<ReactionOutcome>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReactionTableError {
+	#[error("could not read reaction table: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("could not parse reaction table: {0}")]
+	Parse(#[from] ron::error::SpannedError),
+	#[error("reaction {index} has no products")]
+	EmptyProducts { index: usize },
+	#[error("reaction {index} redefines the reaction between {} and {}", a.name(), b.name())]
+	Duplicate { index: usize, a: MoleculeKind, b: MoleculeKind },
+}
+
+#[derive(Default)]
+struct ReactionTableLoader;
+
+impl AssetLoader for ReactionTableLoader {
+	type Asset = ReactionTable;
+	type Settings = ();
+	type Error = ReactionTableError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let file = ron::de::from_bytes::<ReactionTableFile>(&bytes)?;
+
+		let mut reactions = HashMap::new();
+		for (index, entry) in file.reactions.into_iter().enumerate() {
+			let (a, b) = entry.reactants;
+			if entry.products.is_empty() {
+				return Err(ReactionTableError::EmptyProducts { index });
+			}
+			let (a, b) = (a.min(b), a.max(b));
+			if reactions.insert((a, b), entry.products).is_some() {
+				return Err(ReactionTableError::Duplicate { index, a, b });
+			}
+		}
+
+		Ok(ReactionTable { reactions })
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["reactions.ron"]
+	}
+}
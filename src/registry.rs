@@ -0,0 +1,185 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_asset_loader::prelude::*;
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::loading::TextureAssets;
+use crate::GameState;
+
+/// What the molecules' sprites are loaded as, into [`TextureAssets::molecules`]
+pub const MOLECULE_SPRITES_KEY: &str = "molecule_sprites";
+
+/// Every molecule in the game, a new molecule only needs a new entry here
+/// Data files refer to molecules by `name`
+pub const MOLECULE_DEFINITIONS: &[MoleculeDefinition] = &[
+	MoleculeDefinition {
+		name: "star",
+		radius: 10.0,
+		mass: 6.0,
+		colour: Color::hsv(32.0, 0.14, 0.77),
+		sprite: "textures/star.png",
+		score: 1.0,
+	},
+	MoleculeDefinition {
+		name: "ball",
+		radius: 12.0,
+		mass: 8.0,
+		colour: Color::hsv(60.0, 0.82, 0.45),
+		sprite: "textures/ball.png",
+		score: 2.0,
+	},
+	MoleculeDefinition {
+		name: "plus",
+		radius: 16.0,
+		mass: 10.0,
+		colour: Color::hsv(53.0, 0.88, 0.74),
+		sprite: "textures/plus.png",
+		score: 3.0,
+	},
+	MoleculeDefinition {
+		name: "atom",
+		radius: 20.0,
+		mass: 12.0,
+		colour: Color::hsv(10.0, 0.77, 0.75),
+		sprite: "textures/atom.png",
+		score: 4.0,
+	},
+	MoleculeDefinition {
+		name: "hoop",
+		radius: 24.0,
+		mass: 16.0,
+		colour: Color::hsv(354.0, 0.45, 0.80),
+		sprite: "textures/hoop.png",
+		score: 5.0,
+	},
+];
+
+pub struct MoleculeDefinition {
+	pub name: &'static str,
+	pub radius: f32,
+	pub mass: f32,
+	pub colour: Color,
+	/// Path of its image in the assets folder
+	pub sprite: &'static str,
+	/// Points for destroying the molecule with the weapon
+	pub score: f32,
+}
+
+pub struct RegistryPlugin;
+
+impl Plugin for RegistryPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<DynamicAssets>();
+		app.world_mut().resource_mut::<DynamicAssets>().register_asset(MOLECULE_SPRITES_KEY, Box::new(MoleculeSprites));
+		app.add_systems(OnExit(GameState::Loading), setup_registry);
+	}
+}
+
+/// The sprite of every molecule in [`MOLECULE_DEFINITIONS`], in the same order, loaded with the other textures
+#[derive(Debug)]
+struct MoleculeSprites;
+
+impl MoleculeSprites {
+	fn handles(asset_server: &AssetServer) -> Vec<UntypedHandle> {
+		MOLECULE_DEFINITIONS.iter().map(|definition| asset_server.load::<Image>(definition.sprite).untyped()).collect()
+	}
+}
+
+impl DynamicAsset for MoleculeSprites {
+	fn load(&self, asset_server: &AssetServer) -> Vec<UntypedHandle> {
+		MoleculeSprites::handles(asset_server)
+	}
+
+	fn build(&self, world: &mut World) -> Result<DynamicAssetType, anyhow::Error> {
+		Ok(DynamicAssetType::Collection(MoleculeSprites::handles(world.resource::<AssetServer>())))
+	}
+}
+
+/// A type of molecule, stored as its position in [`MOLECULE_DEFINITIONS`]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub struct MoleculeKind(usize);
+
+impl MoleculeKind {
+	pub fn from_name(name: &str) -> Option<Self> {
+		MOLECULE_DEFINITIONS.iter()
+			.position(|definition| definition.name == name)
+			.map(MoleculeKind)
+	}
+
+	pub fn name(self) -> &'static str {
+		MOLECULE_DEFINITIONS[self.0].name
+	}
+}
+
+impl Serialize for MoleculeKind {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.serialize_str(self.name())
+	}
+}
+
+impl<'de> Deserialize<'de> for MoleculeKind {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct KindVisitor;
+
+		impl de::Visitor<'_> for KindVisitor {
+			type Value = MoleculeKind;
+
+			fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+				formatter.write_str("a molecule name")
+			}
+
+			fn visit_str<E: de::Error>(self, name: &str) -> Result<MoleculeKind, E> {
+				MoleculeKind::from_name(name).ok_or_else(|| {
+					let names: Vec<_> = MOLECULE_DEFINITIONS.iter().map(|definition| definition.name).collect();
+					E::custom(format!("unknown molecule `{name}`, expected one of {}", names.join(", ")))
+				})
+			}
+		}
+
+		deserializer.deserialize_str(KindVisitor)
+	}
+}
+
+/// Gameplay values of a molecule, with a handle to its sprite
+pub struct MoleculeKindInfo {
+	pub name: &'static str,
+	pub radius: f32,
+	pub mass: f32,
+	pub colour: Color,
+	pub sprite: Handle<Image>,
+	pub score: f32,
+}
+
+#[derive(Resource)]
+pub struct MoleculeRegistry {
+	kinds: Vec<MoleculeKindInfo>,
+}
+
+impl MoleculeRegistry {
+	pub fn get(&self, kind: MoleculeKind) -> &MoleculeKindInfo {
+		&self.kinds[kind.0]
+	}
+
+	pub fn kinds(&self) -> impl Iterator<Item = MoleculeKind> {
+		(0..self.kinds.len()).map(MoleculeKind)
+	}
+
+	pub fn max_radius(&self) -> f32 {
+		self.kinds.iter().map(|info| info.radius).fold(0.0, f32::max)
+	}
+}
+
+fn setup_registry(mut commands: Commands, textures: Res<TextureAssets>) {
+	commands.insert_resource(MoleculeRegistry {
+		kinds: MOLECULE_DEFINITIONS.iter().enumerate().map(|(index, definition)| MoleculeKindInfo {
+			name: definition.name,
+			radius: definition.radius,
+			mass: definition.mass,
+			colour: definition.colour,
+			// Headless runs load no textures
+			sprite: textures.molecules.get(index).cloned().unwrap_or_default(),
+			score: definition.score,
+		}).collect(),
+	});
+}
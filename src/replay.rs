@@ -0,0 +1,266 @@
+use bevy::prelude::*;
+use thiserror::Error;
+
+use crate::editor::PlayTest;
+use crate::input::{consume_input, PlayerInput};
+use crate::save;
+use crate::simulation::{seed_run, RunSeed, SimulationSet};
+use crate::weapons::{choose_starting_weapon, Loadout, MAX_NAME_LENGTH};
+use crate::GameState;
+
+/// Save the most recent run is kept in
+const LAST_REPLAY_SAVE: &str = "last.replay";
+const REPLAY_MAGIC: &[u8; 4] = b"MRRP";
+/// Version 3 added the starting weapon, version 2 added movement and aim
+/// Older files are the same layout without them
+const REPLAY_VERSION: u8 = 3;
+
+pub struct ReplayPlugin;
+
+/// This plugin records the input of every tick of a run so it can be played back exactly
+/// While a [`ReplayPlayback`] exists, recorded input is fed to the simulation instead of the mouse
+impl Plugin for ReplayPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<LastReplay>()
+			.add_systems(Startup, load_last_replay)
+			.add_systems(OnEnter(GameState::Playing), (start_recording.after(seed_run).after(choose_starting_weapon), spawn_replay_banner))
+			.add_systems(FixedUpdate, (
+				play_replay_input.run_if(resource_exists::<ReplayPlayback>),
+				record_input.after(consume_input).run_if(resource_exists::<ReplayRecorder>),
+			).in_set(SimulationSet::Input))
+			.add_systems(OnExit(GameState::Playing), finish_run)
+			;
+	}
+}
+
+/// Everything needed to reproduce a run: its seed, starting weapon and the input of each tick
+#[derive(Clone, Default, PartialEq, Debug)]
+pub struct Replay {
+	pub seed: u64,
+	/// Empty for replays recorded before weapons could be chosen, which start with the default one
+	pub weapon: String,
+	pub ticks: Vec<PlayerInput>,
+}
+
+#[derive(Debug, Error)]
+pub enum ReplayError {
+	#[error("could not read replay: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("not a replay file")]
+	BadMagic,
+	#[error("unsupported replay version {0}")]
+	UnsupportedVersion(u8),
+	#[error("replay ends unexpectedly")]
+	Truncated,
+	#[error("replay weapon name is not valid UTF-8")]
+	BadWeapon,
+	#[error("weapon name {0} is too long for a replay")]
+	WeaponTooLong(String),
+}
+
+impl Replay {
+	/// Encodes the replay, storing runs of identical ticks once
+	pub fn to_bytes(&self) -> Result<Vec<u8>, ReplayError> {
+		let mut runs: Vec<(u16, PlayerInput)> = vec![];
+		for &input in self.ticks.iter() {
+			match runs.last_mut() {
+				Some((count, last)) if *last == input && *count < u16::MAX => *count += 1,
+				_ => runs.push((1, input)),
+			}
+		}
+
+		// Weapon names are checked to fit when the weapons are loaded, so this only fails for hand made replays
+		if self.weapon.len() > MAX_NAME_LENGTH {
+			return Err(ReplayError::WeaponTooLong(self.weapon.clone()));
+		}
+		let weapon = self.weapon.as_bytes();
+		let mut bytes = Vec::with_capacity(18 + weapon.len() + runs.len() * 11);
+		bytes.extend_from_slice(REPLAY_MAGIC);
+		bytes.push(REPLAY_VERSION);
+		bytes.extend_from_slice(&self.seed.to_le_bytes());
+		bytes.push(weapon.len() as u8);
+		bytes.extend_from_slice(weapon);
+		bytes.extend_from_slice(&(runs.len() as u32).to_le_bytes());
+		for (count, input) in runs {
+			bytes.extend_from_slice(&count.to_le_bytes());
+			let has_movement = input.movement != Vec2::ZERO;
+			bytes.push(input.target.is_some() as u8 | (input.swing as u8) << 1 | (has_movement as u8) << 2 | (input.aim.is_some() as u8) << 3);
+			let vectors = [input.target, has_movement.then_some(input.movement), input.aim];
+			for vector in vectors.into_iter().flatten() {
+				bytes.extend_from_slice(&vector.x.to_le_bytes());
+				bytes.extend_from_slice(&vector.y.to_le_bytes());
+			}
+		}
+		Ok(bytes)
+	}
+
+	pub fn from_bytes(bytes: &[u8]) -> Result<Self, ReplayError> {
+		let mut reader = ByteReader(bytes);
+		if reader.take::<4>()? != *REPLAY_MAGIC {
+			return Err(ReplayError::BadMagic);
+		}
+		let [version] = reader.take::<1>()?;
+		if !(1..=REPLAY_VERSION).contains(&version) {
+			return Err(ReplayError::UnsupportedVersion(version));
+		}
+		let seed = u64::from_le_bytes(reader.take()?);
+		let weapon = if version >= 3 {
+			let [length] = reader.take::<1>()?;
+			let name = reader.take_slice(length as usize)?;
+			String::from_utf8(name.to_vec()).map_err(|_| ReplayError::BadWeapon)?
+		} else {
+			String::new()
+		};
+		let run_count = u32::from_le_bytes(reader.take()?);
+
+		let mut ticks = vec![];
+		for _ in 0..run_count {
+			let count = u16::from_le_bytes(reader.take()?);
+			let [flags] = reader.take::<1>()?;
+			let mut vector = |bit: u8| -> Result<Option<Vec2>, ReplayError> {
+				if flags & bit == 0 {
+					return Ok(None);
+				}
+				Ok(Some(Vec2::new(f32::from_le_bytes(reader.take()?), f32::from_le_bytes(reader.take()?))))
+			};
+			let input = PlayerInput {
+				target: vector(1)?,
+				movement: vector(4)?.unwrap_or_default(),
+				aim: vector(8)?,
+				swing: flags & 2 != 0,
+			};
+			ticks.extend(std::iter::repeat_n(input, count as usize));
+		}
+		Ok(Replay { seed, weapon, ticks })
+	}
+}
+
+struct ByteReader<'a>(&'a [u8]);
+
+impl ByteReader<'_> {
+	fn take<const N: usize>(&mut self) -> Result<[u8; N], ReplayError> {
+		let (head, rest) = self.0.split_first_chunk::<N>().ok_or(ReplayError::Truncated)?;
+		self.0 = rest;
+		Ok(*head)
+	}
+
+	fn take_slice(&mut self, length: usize) -> Result<&[u8], ReplayError> {
+		if self.0.len() < length {
+			return Err(ReplayError::Truncated);
+		}
+		let (head, rest) = self.0.split_at(length);
+		self.0 = rest;
+		Ok(head)
+	}
+}
+
+/// The most recently recorded run, offered on the Retry screen
+#[derive(Resource, Default)]
+pub struct LastReplay(pub Option<Replay>);
+
+#[derive(Resource)]
+struct ReplayRecorder(Replay);
+
+/// Present while a replay is being watched
+#[derive(Resource)]
+pub struct ReplayPlayback {
+	replay: Replay,
+	tick: usize,
+}
+
+impl ReplayPlayback {
+	/// Prepares the next run to replay `replay`, set the state to [`GameState::Playing`] to start it
+	pub fn start(commands: &mut Commands, seed: &mut RunSeed, loadout: &mut Loadout, replay: Replay) {
+		seed.next = Some(replay.seed);
+		loadout.next = Some(replay.weapon.clone());
+		// Replays are always of the loaded level
+		commands.remove_resource::<PlayTest>();
+		commands.insert_resource(ReplayPlayback { replay, tick: 0 });
+	}
+}
+
+#[derive(Component)]
+struct ReplayBanner;
+
+fn load_last_replay(mut last_replay: ResMut<LastReplay>) {
+	match save::load_bytes(LAST_REPLAY_SAVE) {
+		Ok(Some(bytes)) => match Replay::from_bytes(&bytes) {
+			Ok(replay) => last_replay.0 = Some(replay),
+			Err(error) => warn!("Ignoring {LAST_REPLAY_SAVE}: {error}"),
+		},
+		Ok(None) => {}
+		Err(error) => warn!("Could not load {LAST_REPLAY_SAVE}: {error}"),
+	}
+}
+
+fn start_recording(
+	mut commands: Commands,
+	seed: Res<RunSeed>,
+	loadout: Res<Loadout>,
+	playback: Option<Res<ReplayPlayback>>,
+	play_test: Option<Res<PlayTest>>,
+) {
+	if playback.is_none() && play_test.is_none() {
+		commands.insert_resource(ReplayRecorder(Replay { seed: seed.current, weapon: loadout.current.clone(), ticks: vec![] }));
+	}
+}
+
+fn record_input(
+	mut recorder: ResMut<ReplayRecorder>,
+	input: Res<PlayerInput>,
+) {
+	recorder.0.ticks.push(*input);
+}
+
+fn play_replay_input(
+	mut playback: ResMut<ReplayPlayback>,
+	mut input: ResMut<PlayerInput>,
+) {
+	*input = playback.replay.ticks.get(playback.tick).copied().unwrap_or_default();
+	playback.tick += 1;
+}
+
+fn spawn_replay_banner(
+	mut commands: Commands,
+	playback: Option<Res<ReplayPlayback>>,
+) {
+	if playback.is_none() {return};
+	commands.spawn((
+		Text::new("REPLAY"),
+		TextFont {
+			font_size: 25.0,
+			..default()
+		},
+		TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+		Node {
+			position_type: PositionType::Absolute,
+			right: Val::Px(60.0),
+			bottom: Val::Px(30.0),
+			..default()
+		},
+		ReplayBanner,
+	));
+}
+
+fn finish_run(
+	mut commands: Commands,
+	mut last_replay: ResMut<LastReplay>,
+	recorder: Option<ResMut<ReplayRecorder>>,
+	banner_query: Query<Entity, With<ReplayBanner>>,
+) {
+	commands.remove_resource::<ReplayPlayback>();
+	commands.remove_resource::<ReplayRecorder>();
+	for entity in banner_query.iter() {
+		commands.entity(entity).despawn();
+	}
+	if let Some(mut recorder) = recorder {
+		let replay = std::mem::take(&mut recorder.0);
+		match replay.to_bytes() {
+			Ok(bytes) => if let Err(error) = save::store_bytes(LAST_REPLAY_SAVE, &bytes) {
+				warn!("Could not save {LAST_REPLAY_SAVE}: {error}");
+			},
+			Err(error) => warn!("Could not save replay: {error}"),
+		}
+		last_replay.0 = Some(replay);
+	}
+}
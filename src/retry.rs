@@ -1,5 +1,9 @@
+use crate::leaderboard::spawn_leaderboard_panel;
 use crate::loading::TextureAssets;
 use crate::menu::DeathFadeout;
+use crate::replay::{LastReplay, ReplayPlayback};
+use crate::simulation::RunSeed;
+use crate::weapons::Loadout;
 use crate::GameState;
 
 use bevy::prelude::*;
@@ -33,7 +37,11 @@ impl Default for ButtonColors {
 #[derive(Component)]
 struct Retry;
 
-fn setup_retry(mut commands: Commands, textures: Res<TextureAssets>) {
+fn setup_retry(
+	mut commands: Commands,
+	textures: Res<TextureAssets>,
+	last_replay: Res<LastReplay>,
+) {
     commands.spawn((
 		Sprite {
             image: textures.ditheredbackground.clone(),
@@ -121,6 +129,66 @@ fn setup_retry(mut commands: Commands, textures: Res<TextureAssets>) {
 				TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
 			));
 	});
+	spawn_leaderboard_panel(
+		&mut commands,
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Px(30.0),
+			width: Val::Px(520.0),
+			margin: UiRect::left(Val::Px(-260.0)),
+			..default()
+		},
+		Retry,
+	);
+	if last_replay.0.is_some() {
+		commands
+		.spawn((
+			Node {
+				position_type: PositionType::Absolute,
+				left: Val::Percent(50.0),
+				top: Val::Percent(60.0),
+				width: Val::Px(202.0),
+				height: Val::Px(50.0),
+				margin: UiRect {
+					left: Val::Px(-101.0),
+					top: Val::Px(-25.0),
+					..default()
+				},
+				justify_content: JustifyContent::Center,
+				align_items: AlignItems::Center,
+				..default()
+			},
+			Retry,
+		))
+		.with_children(|children| {
+			let button_colors = ButtonColors::default();
+			children
+				.spawn((
+					Button,
+					Node {
+						width: Val::Px(202.0),
+						height: Val::Px(50.0),
+						border: UiRect::all(Val::Px(2.0)),
+						justify_content: JustifyContent::Center,
+						align_items: AlignItems::Center,
+						..Default::default()
+					},
+					BorderColor(Color::linear_rgb(0.4, 0.64, 0.72)),
+					BackgroundColor(button_colors.normal),
+					button_colors,
+					WatchReplay,
+				))
+				.with_child((
+					Text::new("WATCH REPLAY"),
+					TextFont {
+						font_size: 25.0,
+						..default()
+					},
+					TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+				));
+		});
+	}
     commands
         .spawn((
             Node {
@@ -227,8 +295,16 @@ struct ChangeState(GameState);
 #[derive(Component)]
 struct OpenLink(&'static str);
 
+/// Replays the last recorded run
+#[derive(Component)]
+struct WatchReplay;
+
 fn click_retry_button(
+    mut commands: Commands,
     mut next_state: ResMut<NextState<GameState>>,
+    mut seed: ResMut<RunSeed>,
+    mut loadout: ResMut<Loadout>,
+    last_replay: Res<LastReplay>,
     mut interaction_query: Query<
         (
             &Interaction,
@@ -236,15 +312,21 @@ fn click_retry_button(
             &ButtonColors,
             Option<&ChangeState>,
             Option<&OpenLink>,
+            Has<WatchReplay>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, button_colors, change_state, open_link) in &mut interaction_query {
+    for (interaction, mut color, button_colors, change_state, open_link, watch_replay) in &mut interaction_query {
         match *interaction {
             Interaction::Pressed => {
                 if let Some(state) = change_state {
                     next_state.set(state.0.clone());
+                } else if watch_replay {
+                    if let Some(replay) = &last_replay.0 {
+                        ReplayPlayback::start(&mut commands, &mut seed, &mut loadout, replay.clone());
+                        next_state.set(GameState::Playing);
+                    }
                 } else if let Some(link) = open_link {
                     if let Err(error) = webbrowser::open(link.0) {
                         warn!("Failed to open link {error:?}");
@@ -0,0 +1,118 @@
+use serde::{de::DeserializeOwned, Serialize};
+use thiserror::Error;
+
+/// Small save files, kept in the platform data directory on desktop and in local storage in the browser
+#[derive(Debug, Error)]
+pub enum SaveError {
+	#[cfg(not(target_arch = "wasm32"))]
+	#[error("no data directory on this platform")]
+	NoDataDir,
+	#[cfg(not(target_arch = "wasm32"))]
+	#[error("{0}")]
+	Io(#[from] std::io::Error),
+	#[cfg(target_arch = "wasm32")]
+	#[error("browser storage unavailable")]
+	NoStorage,
+	#[cfg(target_arch = "wasm32")]
+	#[error("save is not valid hex")]
+	BadHex,
+	#[error("could not parse save: {0}")]
+	Parse(#[from] ron::error::SpannedError),
+	#[error("could not write save: {0}")]
+	Serialize(#[from] ron::Error),
+}
+
+/// Reads the save called `name`, `Ok(None)` means it has never been written
+pub fn load<T: DeserializeOwned>(name: &str) -> Result<Option<T>, SaveError> {
+	let Some(contents) = read(name)? else {return Ok(None)};
+	Ok(Some(ron::from_str(&contents)?))
+}
+
+pub fn store<T: Serialize>(name: &str, value: &T) -> Result<(), SaveError> {
+	let contents = ron::ser::to_string_pretty(value, ron::ser::PrettyConfig::default())?;
+	write(name, &contents)
+}
+
+/// Reads a binary save, `Ok(None)` means it has never been written
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_bytes(name: &str) -> Result<Option<Vec<u8>>, SaveError> {
+	match std::fs::read(data_dir()?.join(name)) {
+		Ok(bytes) => Ok(Some(bytes)),
+		Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(error) => Err(error.into()),
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub fn store_bytes(name: &str, bytes: &[u8]) -> Result<(), SaveError> {
+	write(name, bytes)
+}
+
+/// Reads a binary save, `Ok(None)` means it has never been written
+/// Local storage only holds strings, so binary saves are kept there as hex
+#[cfg(target_arch = "wasm32")]
+pub fn load_bytes(name: &str) -> Result<Option<Vec<u8>>, SaveError> {
+	let Some(hex) = read(name)? else {return Ok(None)};
+	if hex.len() % 2 != 0 {
+		return Err(SaveError::BadHex);
+	}
+	(0..hex.len()).step_by(2)
+		.map(|i| hex.get(i..i + 2).and_then(|pair| u8::from_str_radix(pair, 16).ok()).ok_or(SaveError::BadHex))
+		.collect::<Result<_, _>>()
+		.map(Some)
+}
+
+#[cfg(target_arch = "wasm32")]
+pub fn store_bytes(name: &str, bytes: &[u8]) -> Result<(), SaveError> {
+	let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+	write(name, &hex)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn data_dir() -> Result<std::path::PathBuf, SaveError> {
+	directories::ProjectDirs::from("", "SoysCodingCafe", "Mole Rancher Demake")
+		.map(|dirs| dirs.data_dir().to_path_buf())
+		.ok_or(SaveError::NoDataDir)
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read(name: &str) -> Result<Option<String>, SaveError> {
+	match std::fs::read_to_string(data_dir()?.join(name)) {
+		Ok(contents) => Ok(Some(contents)),
+		Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+		Err(error) => Err(error.into()),
+	}
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write(name: &str, contents: impl AsRef<[u8]>) -> Result<(), SaveError> {
+	let dir = data_dir()?;
+	std::fs::create_dir_all(&dir)?;
+	// Written beside the save and renamed over it, so a crash mid-write can't corrupt the old one
+	let temp = dir.join(format!("{name}.tmp"));
+	std::fs::write(&temp, contents)?;
+	std::fs::rename(temp, dir.join(name))?;
+	Ok(())
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage() -> Result<web_sys::Storage, SaveError> {
+	web_sys::window()
+		.and_then(|window| window.local_storage().ok().flatten())
+		.ok_or(SaveError::NoStorage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn storage_key(name: &str) -> String {
+	format!("mole_rancher_demake/{name}")
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read(name: &str) -> Result<Option<String>, SaveError> {
+	storage()?.get_item(&storage_key(name)).map_err(|_| SaveError::NoStorage)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write(name: &str, contents: &str) -> Result<(), SaveError> {
+	storage()?.set_item(&storage_key(name), contents).map_err(|_| SaveError::NoStorage)
+}
@@ -0,0 +1,342 @@
+use bevy::prelude::*;
+use bevy::window::{MonitorSelection, PrimaryWindow, WindowMode};
+use serde::{Deserialize, Serialize};
+
+use crate::input::ControlScheme;
+use crate::loading::TextureAssets;
+use crate::save;
+use crate::ui::{button, button_label, spawn_screen_background, ButtonColors};
+use crate::GameState;
+
+const SETTINGS_SAVE: &str = "settings.ron";
+/// How far one press of `-` or `+` moves a slider
+const SLIDER_STEP: f32 = 0.1;
+
+pub struct SettingsPlugin;
+
+/// This plugin loads the player's settings on startup and runs the settings screen
+/// Each setting is applied by the plugin that owns what it changes, whenever [`Settings`] changes
+impl Plugin for SettingsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_resource::<Settings>()
+			.add_systems(Startup, load_settings)
+			.add_systems(Update, apply_fullscreen.run_if(resource_changed::<Settings>))
+			.add_systems(OnEnter(GameState::Settings), setup_settings)
+			.add_systems(Update, (click_settings_button, update_setting_values).chain().run_if(in_state(GameState::Settings)))
+			.add_systems(OnExit(GameState::Settings), (save_settings, cleanup_settings))
+			;
+	}
+}
+
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+// Settings added in later versions fall back to their defaults when loading an older file
+#[serde(default)]
+pub struct Settings {
+	/// `0.0` to `1.0`, scales the music
+	pub music_volume: f32,
+	/// `0.0` to `1.0`, scales every sound effect
+	pub sfx_volume: f32,
+	/// `0.0` to `1.0`, scales the screen curvature and scanlines
+	pub crt_strength: f32,
+	pub screen_shake: bool,
+	pub fullscreen: bool,
+	pub controls: ControlScheme,
+}
+
+impl Default for Settings {
+	fn default() -> Self {
+		Settings {
+			music_volume: 1.0,
+			sfx_volume: 1.0,
+			crt_strength: 1.0,
+			screen_shake: true,
+			fullscreen: false,
+			controls: ControlScheme::Auto,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Setting {
+	MusicVolume,
+	SfxVolume,
+	CrtStrength,
+	ScreenShake,
+	Fullscreen,
+	Controls,
+}
+
+impl Setting {
+	const ALL: [Setting; 6] = [
+		Setting::MusicVolume,
+		Setting::SfxVolume,
+		Setting::CrtStrength,
+		Setting::ScreenShake,
+		Setting::Fullscreen,
+		Setting::Controls,
+	];
+
+	fn label(self) -> &'static str {
+		match self {
+			Setting::MusicVolume => "MUSIC VOLUME",
+			Setting::SfxVolume => "SFX VOLUME",
+			Setting::CrtStrength => "CRT EFFECT",
+			Setting::ScreenShake => "SCREEN SHAKE",
+			Setting::Fullscreen => "FULLSCREEN",
+			Setting::Controls => "CONTROLS",
+		}
+	}
+
+	fn is_slider(self) -> bool {
+		matches!(self, Setting::MusicVolume | Setting::SfxVolume | Setting::CrtStrength)
+	}
+
+	fn slider(self, settings: &mut Settings) -> Option<&mut f32> {
+		match self {
+			Setting::MusicVolume => Some(&mut settings.music_volume),
+			Setting::SfxVolume => Some(&mut settings.sfx_volume),
+			Setting::CrtStrength => Some(&mut settings.crt_strength),
+			Setting::ScreenShake | Setting::Fullscreen | Setting::Controls => None,
+		}
+	}
+
+	fn toggle(self, settings: &mut Settings) -> Option<&mut bool> {
+		match self {
+			Setting::ScreenShake => Some(&mut settings.screen_shake),
+			Setting::Fullscreen => Some(&mut settings.fullscreen),
+			Setting::MusicVolume | Setting::SfxVolume | Setting::CrtStrength | Setting::Controls => None,
+		}
+	}
+
+	/// Moves a setting with more than two values on to the next one
+	fn cycle(self, settings: &mut Settings) {
+		if self == Setting::Controls {
+			settings.controls = match settings.controls {
+				ControlScheme::Auto => ControlScheme::Mouse,
+				ControlScheme::Mouse => ControlScheme::Keyboard,
+				ControlScheme::Keyboard => ControlScheme::Gamepad,
+				ControlScheme::Gamepad => ControlScheme::Touch,
+				ControlScheme::Touch => ControlScheme::Auto,
+			};
+		}
+	}
+
+	fn value(self, settings: &Settings) -> String {
+		let percent = |value: f32| format!("{:.0}%", value * 100.0);
+		let on_off = |value: bool| if value {"ON".to_string()} else {"OFF".to_string()};
+		match self {
+			Setting::MusicVolume => percent(settings.music_volume),
+			Setting::SfxVolume => percent(settings.sfx_volume),
+			Setting::CrtStrength => percent(settings.crt_strength),
+			Setting::ScreenShake => on_off(settings.screen_shake),
+			Setting::Fullscreen => on_off(settings.fullscreen),
+			Setting::Controls => match settings.controls {
+				ControlScheme::Auto => "AUTO",
+				ControlScheme::Mouse => "MOUSE",
+				ControlScheme::Keyboard => "KEYS",
+				ControlScheme::Gamepad => "PAD",
+				ControlScheme::Touch => "TOUCH",
+			}.to_string(),
+		}
+	}
+}
+
+#[derive(Component, Clone)]
+struct SettingsScreen;
+
+#[derive(Component)]
+struct SettingValue(Setting);
+
+#[derive(Component, Clone, Copy)]
+enum SettingsButton {
+	Adjust(Setting, f32),
+	Toggle(Setting),
+	Bindings,
+	Back,
+}
+
+fn load_settings(mut settings: ResMut<Settings>) {
+	match save::load::<Settings>(SETTINGS_SAVE) {
+		Ok(Some(saved)) => {
+			// A hand edited or corrupt save can hold values the sliders never make
+			let defaults = Settings::default();
+			let slider = |value: f32, default: f32| if value.is_nan() {default} else {value.clamp(0.0, 1.0)};
+			*settings = Settings {
+				music_volume: slider(saved.music_volume, defaults.music_volume),
+				sfx_volume: slider(saved.sfx_volume, defaults.sfx_volume),
+				crt_strength: slider(saved.crt_strength, defaults.crt_strength),
+				..saved
+			};
+		}
+		Ok(None) => (),
+		Err(error) => warn!("Could not load settings, using the defaults: {error}"),
+	}
+}
+
+fn save_settings(settings: Res<Settings>) {
+	if let Err(error) = save::store(SETTINGS_SAVE, &*settings) {
+		warn!("Could not save settings: {error}");
+	}
+}
+
+fn apply_fullscreen(
+	mut window_query: Query<&mut Window, With<PrimaryWindow>>,
+	settings: Res<Settings>,
+) {
+	let Ok(mut window) = window_query.single_mut() else {return};
+	let mode = if settings.fullscreen {
+		WindowMode::BorderlessFullscreen(MonitorSelection::Current)
+	} else {
+		WindowMode::Windowed
+	};
+	if window.mode != mode {
+		window.mode = mode;
+	}
+}
+
+fn spawn_button(parent: &mut ChildSpawnerCommands, width: f32, label: &str, marker: SettingsButton) {
+	parent
+		.spawn(button(Node {width: Val::Px(width), height: Val::Px(40.0), ..default()}, marker))
+		.with_child(button_label(label, 25.0));
+}
+
+fn setup_settings(mut commands: Commands, textures: Res<TextureAssets>) {
+	spawn_screen_background(&mut commands, &textures, SettingsScreen);
+	commands
+	.spawn((
+		Node {
+			position_type: PositionType::Absolute,
+			width: Val::Percent(100.0),
+			height: Val::Percent(100.0),
+			flex_direction: FlexDirection::Column,
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			row_gap: Val::Px(16.0),
+			..default()
+		},
+		SettingsScreen,
+	))
+	.with_children(|children| {
+		children.spawn((
+			Text::new("SETTINGS"),
+			TextFont {
+				font_size: 50.0,
+				..default()
+			},
+			TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+		));
+		for setting in Setting::ALL {
+			children
+				.spawn((
+					Node {
+						width: Val::Px(560.0),
+						padding: UiRect::axes(Val::Px(12.0), Val::Px(6.0)),
+						align_items: AlignItems::Center,
+						column_gap: Val::Px(10.0),
+						..default()
+					},
+					BackgroundColor(Color::linear_rgba(0.0, 0.0, 0.0, 0.6)),
+				))
+				.with_children(|row| {
+					row.spawn((
+						Text::new(setting.label()),
+						TextFont {
+							font_size: 25.0,
+							..default()
+						},
+						TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+						Node {
+							flex_grow: 1.0,
+							..default()
+						},
+					));
+					if setting.is_slider() {
+						spawn_button(row, 40.0, "-", SettingsButton::Adjust(setting, -SLIDER_STEP));
+					}
+					row.spawn((
+						Text::default(),
+						TextFont {
+							font_size: 25.0,
+							..default()
+						},
+						TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+						TextLayout::new_with_justify(JustifyText::Center),
+						Node {
+							width: Val::Px(80.0),
+							..default()
+						},
+						SettingValue(setting),
+					));
+					if setting.is_slider() {
+						spawn_button(row, 40.0, "+", SettingsButton::Adjust(setting, SLIDER_STEP));
+					} else {
+						let label = if setting == Setting::Controls {"NEXT"} else {"TOGGLE"};
+						spawn_button(row, 90.0, label, SettingsButton::Toggle(setting));
+					}
+				});
+		}
+		children
+			.spawn(Node {
+				column_gap: Val::Px(16.0),
+				..default()
+			})
+			.with_children(|row| {
+				spawn_button(row, 202.0, "BINDINGS", SettingsButton::Bindings);
+				spawn_button(row, 202.0, "BACK", SettingsButton::Back);
+			});
+	});
+}
+
+fn click_settings_button(
+	mut next_state: ResMut<NextState<GameState>>,
+	mut settings: ResMut<Settings>,
+	mut interaction_query: Query<
+		(&Interaction, &mut BackgroundColor, &ButtonColors, &SettingsButton),
+		(Changed<Interaction>, With<Button>),
+	>,
+) {
+	for (interaction, mut color, button_colors, button) in &mut interaction_query {
+		match *interaction {
+			Interaction::Pressed => match *button {
+				SettingsButton::Adjust(setting, step) => {
+					if let Some(value) = setting.slider(&mut settings) {
+						// Rounded so repeated steps land exactly on each step
+						*value = (((*value + step) / SLIDER_STEP).round() * SLIDER_STEP).clamp(0.0, 1.0);
+					}
+				}
+				SettingsButton::Toggle(setting) => {
+					if let Some(value) = setting.toggle(&mut settings) {
+						*value = !*value;
+					} else {
+						setting.cycle(&mut settings);
+					}
+				}
+				SettingsButton::Bindings => next_state.set(GameState::Bindings),
+				SettingsButton::Back => next_state.set(GameState::Menu),
+			},
+			Interaction::Hovered => {
+				*color = button_colors.hovered.into();
+			}
+			Interaction::None => {
+				*color = button_colors.normal.into();
+			}
+		}
+	}
+}
+
+fn update_setting_values(
+	settings: Res<Settings>,
+	mut value_query: Query<(&mut Text, Ref<SettingValue>)>,
+) {
+	for (mut text, value) in value_query.iter_mut() {
+		if settings.is_changed() || value.is_added() {
+			text.0 = value.0.value(&settings);
+		}
+	}
+}
+
+fn cleanup_settings(mut commands: Commands, screen: Query<Entity, With<SettingsScreen>>) {
+	for entity in screen.iter() {
+		commands.entity(entity).despawn();
+	}
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use rand::{distributions::{Distribution, Standard}, rngs::StdRng, Rng, SeedableRng};
+
+use crate::arena::Arena;
+use crate::audio::PlaySfx;
+use crate::camera::CameraShake;
+use crate::molecules::ReactorHit;
+use crate::{GameState, PlayState};
+
+/// Gameplay ticks per second
+pub const TICK_RATE: f64 = 60.0;
+
+pub struct SimulationPlugin;
+
+/// This plugin runs gameplay on a fixed timestep with a seeded random number generator
+/// The same seed and the same inputs each tick always play out the same run
+impl Plugin for SimulationPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_sub_state::<PlayState>()
+			.insert_resource(Time::<Fixed>::from_hz(TICK_RATE))
+			.init_resource::<RunSeed>()
+			.init_resource::<Arena>()
+			.insert_resource(GameRng::seeded(0))
+			.add_event::<PlaySfx>()
+			.add_event::<CameraShake>()
+			.add_event::<ReactorHit>()
+			.configure_sets(FixedUpdate, (
+				SimulationSet::Input,
+				SimulationSet::Player,
+				SimulationSet::Molecules,
+				SimulationSet::Reactor,
+			).chain().run_if(in_state(PlayState::Running)))
+			.add_systems(OnEnter(GameState::Playing), seed_run)
+			;
+	}
+}
+
+/// Gameplay systems in `FixedUpdate` run in this order so every tick is processed identically
+#[derive(SystemSet, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum SimulationSet {
+	Input,
+	Player,
+	Molecules,
+	Reactor,
+}
+
+/// Seed used for the current run
+/// Setting `next` before a run starts plays it with that seed instead of a random one
+#[derive(Resource, Default)]
+pub struct RunSeed {
+	pub current: u64,
+	pub next: Option<u64>,
+}
+
+/// The only source of randomness gameplay systems are allowed to use
+#[derive(Resource)]
+pub struct GameRng(StdRng);
+
+impl GameRng {
+	pub fn seeded(seed: u64) -> Self {
+		GameRng(StdRng::seed_from_u64(seed))
+	}
+
+	pub fn random<T>(&mut self) -> T where Standard: Distribution<T> {
+		self.0.gen()
+	}
+}
+
+pub fn seed_run(
+	mut commands: Commands,
+	mut seed: ResMut<RunSeed>,
+) {
+	seed.current = seed.next.take().unwrap_or_else(rand::random);
+	commands.insert_resource(GameRng::seeded(seed.current));
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+/// Buckets points into square cells so nearby pairs can be found without testing every pair
+/// Cells must be at least as wide as the largest distance that counts as "nearby"
+#[derive(Default)]
+pub struct SpatialHash {
+	cell_size: f32,
+	cells: HashMap<IVec2, Vec<usize>>,
+}
+
+impl SpatialHash {
+	fn cell(&self, pos: Vec2) -> IVec2 {
+		(pos / self.cell_size).floor().as_ivec2()
+	}
+
+	/// Replaces the contents with `positions`, each stored under its index in the slice
+	pub fn rebuild(&mut self, cell_size: f32, positions: &[Vec2]) {
+		self.cell_size = cell_size;
+		// Cells are emptied rather than removed so their allocations are reused next frame
+		for indices in self.cells.values_mut() {
+			indices.clear();
+		}
+		for (index, &pos) in positions.iter().enumerate() {
+			let cell = self.cell(pos);
+			self.cells.entry(cell).or_default().push(index);
+		}
+	}
+
+	/// Collects every pair `(a, b)` with `a < b` whose points share or neighbour a cell
+	/// Pairs are sorted, matching the order of `Query::iter_combinations`
+	pub fn candidate_pairs(&self, positions: &[Vec2], pairs: &mut Vec<(usize, usize)>) {
+		pairs.clear();
+		let mut neighbours = Vec::new();
+		for (a, &pos) in positions.iter().enumerate() {
+			neighbours.clear();
+			let cell = self.cell(pos);
+			for x in -1..=1 {
+				for y in -1..=1 {
+					if let Some(indices) = self.cells.get(&(cell + IVec2::new(x, y))) {
+						neighbours.extend(indices.iter().copied().filter(|&b| b > a));
+					}
+				}
+			}
+			neighbours.sort_unstable();
+			pairs.extend(neighbours.iter().map(|&b| (a, b)));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::simulation::GameRng;
+
+	const CELL_SIZE: f32 = 40.0;
+
+	/// Every pair within `CELL_SIZE` of each other, in the order `Query::iter_combinations` visits them
+	fn nearby_pairs(positions: &[Vec2]) -> Vec<(usize, usize)> {
+		(0..positions.len())
+			.flat_map(|a| (a + 1..positions.len()).map(move |b| (a, b)))
+			.filter(|&(a, b)| positions[a].distance(positions[b]) <= CELL_SIZE)
+			.collect()
+	}
+
+	#[test]
+	fn finds_the_same_pairs_in_the_same_order_as_checking_every_pair() {
+		let mut rng = GameRng::seeded(5);
+		let mut positions: Vec<Vec2> = (0..300)
+			.map(|_| Vec2::new(rng.random::<f32>() - 0.5, rng.random::<f32>() - 0.5) * 600.0)
+			.collect();
+		// Corners and edges of cells, so pairs exactly a cell apart straddle a boundary
+		for x in -3..=3 {
+			for y in -3..=3 {
+				positions.push(Vec2::new(x as f32, y as f32) * CELL_SIZE);
+				positions.push(Vec2::new(x as f32 + 0.5, y as f32) * CELL_SIZE);
+			}
+		}
+
+		let mut spatial_hash = SpatialHash::default();
+		let mut pairs = Vec::new();
+		spatial_hash.rebuild(CELL_SIZE, &positions);
+		spatial_hash.candidate_pairs(&positions, &mut pairs);
+
+		assert!(pairs.windows(2).all(|pair| pair[0] < pair[1]), "candidate pairs are out of order or repeated");
+		let found: Vec<_> = pairs.into_iter()
+			.filter(|&(a, b)| positions[a].distance(positions[b]) <= CELL_SIZE)
+			.collect();
+		assert_eq!(found, nearby_pairs(&positions));
+	}
+
+	#[test]
+	fn rebuilding_forgets_the_old_positions() {
+		let mut spatial_hash = SpatialHash::default();
+		let mut pairs = Vec::new();
+		spatial_hash.rebuild(CELL_SIZE, &[Vec2::ZERO, Vec2::splat(10.0), Vec2::splat(500.0)]);
+		let moved = [Vec2::ZERO, Vec2::splat(500.0)];
+		spatial_hash.rebuild(CELL_SIZE, &moved);
+		spatial_hash.candidate_pairs(&moved, &mut pairs);
+		assert!(pairs.is_empty(), "pairs found from a previous rebuild: {pairs:?}");
+	}
+}
@@ -0,0 +1,141 @@
+use bevy::prelude::*;
+
+use crate::input::{ControlScheme, InputSampler};
+use crate::{GameState, PlayState};
+
+/// Width of the on-screen swing button, in logical pixels
+const SWING_BUTTON_SIZE: f32 = 160.0;
+/// Gap between the swing button and the bottom right corner of the window
+const SWING_BUTTON_MARGIN: f32 = 40.0;
+/// Width of the on-screen pause button, in logical pixels
+const PAUSE_BUTTON_SIZE: f32 = 64.0;
+/// Gap between the pause button and the top left corner of the window
+const PAUSE_BUTTON_MARGIN: f32 = 20.0;
+
+pub struct TouchControlsPlugin;
+
+/// This plugin shows the on-screen swing and pause buttons while playing with a touch screen
+impl Plugin for TouchControlsPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(OnEnter(GameState::Playing), setup_touch_controls)
+			.add_systems(Update, (show_touch_controls, click_pause_button).run_if(in_state(GameState::Playing)))
+			.add_systems(OnExit(GameState::Playing), cleanup_touch_controls)
+			;
+	}
+}
+
+#[derive(Component)]
+struct TouchControls;
+
+#[derive(Component)]
+struct TouchPauseButton;
+
+/// Whether a touch at `position`, in logical window pixels, is on the swing button
+/// `viewport` is the camera's logical viewport, which the UI is laid out in at `ui_scale`
+pub fn on_swing_button(viewport: Rect, ui_scale: f32, position: Vec2) -> bool {
+	let centre = viewport.max - Vec2::splat(SWING_BUTTON_MARGIN + SWING_BUTTON_SIZE / 2.0) * ui_scale;
+	position.distance(centre) <= SWING_BUTTON_SIZE / 2.0 * ui_scale
+}
+
+/// Whether a touch at `position`, in logical window pixels, is on the pause button, laid out like [`on_swing_button`]
+pub fn on_pause_button(viewport: Rect, ui_scale: f32, position: Vec2) -> bool {
+	let min = viewport.min + Vec2::splat(PAUSE_BUTTON_MARGIN) * ui_scale;
+	Rect::from_corners(min, min + Vec2::splat(PAUSE_BUTTON_SIZE) * ui_scale).contains(position)
+}
+
+fn setup_touch_controls(mut commands: Commands) {
+	commands
+		.spawn((
+			Node {
+				position_type: PositionType::Absolute,
+				width: Val::Percent(100.0),
+				height: Val::Percent(100.0),
+				..default()
+			},
+			// Touches are handled by the input sampler, so the overlay mustn't catch them
+			Pickable::IGNORE,
+			Visibility::Hidden,
+			TouchControls,
+		))
+		.with_children(|children| {
+			children
+				.spawn((
+					Node {
+						position_type: PositionType::Absolute,
+						right: Val::Px(SWING_BUTTON_MARGIN),
+						bottom: Val::Px(SWING_BUTTON_MARGIN),
+						width: Val::Px(SWING_BUTTON_SIZE),
+						height: Val::Px(SWING_BUTTON_SIZE),
+						border: UiRect::all(Val::Px(4.0)),
+						justify_content: JustifyContent::Center,
+						align_items: AlignItems::Center,
+						..default()
+					},
+					BorderRadius::MAX,
+					BorderColor(Color::linear_rgba(0.4, 0.64, 0.72, 0.8)),
+					BackgroundColor(Color::linear_rgba(0.15, 0.15, 0.15, 0.4)),
+					Pickable::IGNORE,
+				))
+				.with_child((
+					Text::new("SWING"),
+					TextFont {
+						font_size: 30.0,
+						..default()
+					},
+					TextColor(Color::linear_rgba(0.9, 0.9, 0.9, 0.8)),
+					Pickable::IGNORE,
+				));
+			children
+				.spawn((
+					Button,
+					Node {
+						position_type: PositionType::Absolute,
+						left: Val::Px(PAUSE_BUTTON_MARGIN),
+						top: Val::Px(PAUSE_BUTTON_MARGIN),
+						width: Val::Px(PAUSE_BUTTON_SIZE),
+						height: Val::Px(PAUSE_BUTTON_SIZE),
+						border: UiRect::all(Val::Px(2.0)),
+						justify_content: JustifyContent::Center,
+						align_items: AlignItems::Center,
+						..default()
+					},
+					BorderColor(Color::linear_rgb(0.4, 0.64, 0.72)),
+					BackgroundColor(Color::linear_rgba(0.15, 0.15, 0.15, 0.6)),
+					TouchPauseButton,
+				))
+				.with_child((
+					Text::new("II"),
+					TextFont {
+						font_size: 30.0,
+						..default()
+					},
+					TextColor(Color::linear_rgb(0.9, 0.9, 0.9)),
+				));
+		});
+}
+
+fn show_touch_controls(
+	sampler: Res<InputSampler>,
+	play_state: Res<State<PlayState>>,
+	mut controls_query: Query<&mut Visibility, With<TouchControls>>,
+) {
+	let visible = sampler.scheme() == ControlScheme::Touch && *play_state.get() == PlayState::Running;
+	for mut visibility in controls_query.iter_mut() {
+		visibility.set_if_neq(if visible {Visibility::Inherited} else {Visibility::Hidden});
+	}
+}
+
+fn click_pause_button(
+	mut next_play_state: ResMut<NextState<PlayState>>,
+	interaction_query: Query<&Interaction, (Changed<Interaction>, With<TouchPauseButton>)>,
+) {
+	if interaction_query.iter().any(|interaction| *interaction == Interaction::Pressed) {
+		next_play_state.set(PlayState::Paused);
+	}
+}
+
+fn cleanup_touch_controls(mut commands: Commands, controls: Query<Entity, With<TouchControls>>) {
+	for entity in controls.iter() {
+		commands.entity(entity).despawn();
+	}
+}
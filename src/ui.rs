@@ -0,0 +1,87 @@
+use bevy::prelude::*;
+
+use crate::loading::TextureAssets;
+
+pub const TEXT_COLOUR: Color = Color::linear_rgb(0.9, 0.9, 0.9);
+pub const BORDER_COLOUR: Color = Color::linear_rgb(0.4, 0.64, 0.72);
+
+/// What a button's background shows, its screen's click system swaps between them
+#[derive(Component)]
+pub struct ButtonColors {
+	pub normal: Color,
+	pub hovered: Color,
+}
+
+impl Default for ButtonColors {
+	fn default() -> Self {
+		ButtonColors {
+			normal: Color::linear_rgb(0.15, 0.15, 0.15),
+			hovered: Color::linear_rgb(0.25, 0.25, 0.25),
+		}
+	}
+}
+
+/// A bordered button with its label centred, `node` sizes and places it and `marker` says which button it is
+/// The label is added as a child, see [`button_label`]
+pub fn button(node: Node, marker: impl Bundle) -> impl Bundle {
+	let button_colors = ButtonColors::default();
+	(
+		Button,
+		Node {
+			border: UiRect::all(Val::Px(2.0)),
+			justify_content: JustifyContent::Center,
+			align_items: AlignItems::Center,
+			..node
+		},
+		BorderColor(BORDER_COLOUR),
+		BackgroundColor(button_colors.normal),
+		button_colors,
+		marker,
+	)
+}
+
+pub fn button_label(label: impl Into<String>, font_size: f32) -> impl Bundle {
+	(
+		Text::new(label),
+		TextFont {
+			font_size,
+			..default()
+		},
+		TextColor(TEXT_COLOUR),
+	)
+}
+
+/// The dithered grid behind the settings screens
+pub fn spawn_screen_background(commands: &mut Commands, textures: &TextureAssets, marker: impl Component + Clone) {
+	commands.spawn((
+		Sprite {
+			image: textures.ditheredbackground.clone(),
+			custom_size: Some(Vec2::new(1080.0, 810.0)),
+			..default()
+		},
+		Transform::from_xyz(0.0, 0.0, 1.0),
+		marker.clone(),
+	));
+	for y in (-810.0 as i32 / 2..=810.0 as i32 / 2).step_by(40.0 as usize) {
+		commands.spawn((
+			Sprite {
+				color: BORDER_COLOUR,
+				custom_size: Some(Vec2::new(1080.0, 2.0)),
+				..default()
+			},
+			Transform::from_xyz(0.0, y as f32, 5.0),
+			marker.clone(),
+		));
+	}
+	for x in (-1080.0 as i32 / 2..=1080.0 as i32 / 2).step_by(40.0 as usize) {
+		commands.spawn((
+			Sprite {
+				color: BORDER_COLOUR,
+				custom_size: Some(Vec2::new(2.0, 810.0)),
+				..default()
+			},
+			Transform::from_xyz(x as f32, 0.0, 5.0),
+			marker.clone(),
+		));
+	}
+}
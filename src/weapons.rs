@@ -0,0 +1,378 @@
+use std::collections::HashSet;
+
+use bevy::asset::{io::Reader, AssetLoader, LoadContext};
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::levels::Level;
+use crate::loading::{DataAssets, TextureAssets};
+use crate::molecules::SpawnTracker;
+use crate::player::{PlayerInfo, WeaponPivot, WeaponSprite};
+use crate::ui::{button, button_label, ButtonColors};
+use crate::GameState;
+
+/// How close the player has to get to a pickup to take it
+const PICKUP_RADIUS: f32 = 32.0;
+/// Longest a weapon's name can be in bytes, replays store its length in a single byte
+pub const MAX_NAME_LENGTH: usize = u8::MAX as usize;
+/// The weapons loaded into [`crate::loading::DataAssets`], levels load them as well to check their pickups
+pub const WEAPONS_PATH: &str = "data/default.weapons.ron";
+
+pub struct WeaponsPlugin;
+
+/// This plugin loads the weapon definitions, picks the one each run starts with and cleans up the pickups of each wave
+/// Placing and picking up weapons happens in the molecule and player systems, see [`update_wave_pickups`] and [`pick_up_weapons`]
+impl Plugin for WeaponsPlugin {
+	fn build(&self, app: &mut App) {
+		app.init_asset::<WeaponSet>()
+			.init_asset_loader::<WeaponSetLoader>()
+			.init_resource::<Loadout>()
+			.add_systems(OnEnter(GameState::Playing), choose_starting_weapon)
+			.add_systems(OnExit(GameState::Playing), cleanup_pickups)
+			;
+	}
+}
+
+pub struct WeaponPickerPlugin;
+
+/// This plugin puts a button on the menu to choose the weapon runs start with
+impl Plugin for WeaponPickerPlugin {
+	fn build(&self, app: &mut App) {
+		app.add_systems(OnEnter(GameState::Menu), spawn_weapon_button)
+			.add_systems(Update, click_weapon_button.run_if(in_state(GameState::Menu)))
+			.add_systems(OnExit(GameState::Menu), cleanup_weapon_button)
+			;
+	}
+}
+
+/// Everything that makes one weapon different from another
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct WeaponDefinition {
+	pub name: String,
+	/// Image under `assets/`
+	pub sprite: String,
+	pub sprite_size: Vec2,
+	/// Where the centre of the sprite sits from the player, pointing along the weapon
+	pub sprite_offset: Vec2,
+	/// Degrees swung through from one side to the other
+	pub arc: f32,
+	/// Seconds the swing takes once released
+	pub swing_time: f32,
+	pub windup: Windup,
+	/// Share of the blade's speed passed on to a batted molecule
+	pub knockback: f32,
+	pub blade: Blade,
+}
+
+/// How the weapon is drawn back while the swing button is held
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Windup {
+	/// Furthest the charge goes, in radians added to the swing
+	pub max: f32,
+	/// How quickly the charge closes in on `max`, per second
+	pub rate: f32,
+	/// Degrees the weapon is drawn back at full charge
+	pub angle: f32,
+}
+
+/// The part of the weapon that hits, a line thickened by `radius`
+/// Given in the pivot's space, where the weapon points up, and reaching away from the player
+#[derive(Serialize, Deserialize, Clone, Copy, Debug)]
+pub struct Blade {
+	pub start: Vec2,
+	pub end: Vec2,
+	pub radius: f32,
+}
+
+/// Every weapon that can be chosen or picked up, the first is the default
+#[derive(Asset, TypePath, Deserialize, Debug)]
+pub struct WeaponSet {
+	weapons: Vec<WeaponDefinition>,
+}
+
+impl WeaponSet {
+	pub fn get(&self, name: &str) -> Option<&WeaponDefinition> {
+		self.weapons.iter().find(|weapon| weapon.name == name)
+	}
+
+	/// The weapon called `name`, or the default if there isn't one
+	pub fn get_or_default(&self, name: &str) -> &WeaponDefinition {
+		self.get(name).unwrap_or(&self.weapons[0])
+	}
+
+	/// The weapon after the one called `name`, looping back to the first
+	fn after(&self, name: &str) -> &WeaponDefinition {
+		let index = self.weapons.iter().position(|weapon| weapon.name == name).map_or(0, |index| index + 1);
+		&self.weapons[index % self.weapons.len()]
+	}
+}
+
+/// A weapon lying on the floor during a wave, swapped for the player's when they walk over it
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PickupRecord {
+	pub position: Vec2,
+	pub weapon: String,
+}
+
+/// Which weapon runs start with
+#[derive(Resource, Default)]
+pub struct Loadout {
+	/// Picked on the menu, the default weapon when it isn't one of the set
+	pub chosen: String,
+	/// Overrides `chosen` for the next run only, so a replay starts with the weapon it was recorded with
+	pub next: Option<String>,
+	/// The weapon the current run started with
+	pub current: String,
+}
+
+#[derive(Debug, Error)]
+pub enum WeaponSetError {
+	#[error("could not read weapons: {0}")]
+	Io(#[from] std::io::Error),
+	#[error("could not parse weapons: {0}")]
+	Parse(#[from] ron::error::SpannedError),
+	#[error("there are no weapons")]
+	NoWeapons,
+	#[error("weapon {index} reuses the name {name}")]
+	Duplicate { index: usize, name: String },
+	#[error("weapon {name}: {reason}")]
+	Invalid { name: String, reason: String },
+}
+
+impl WeaponDefinition {
+	fn validate(&self) -> Result<(), WeaponSetError> {
+		let invalid = |reason: &str| Err(WeaponSetError::Invalid { name: self.name.clone(), reason: reason.to_string() });
+		if self.name.len() > MAX_NAME_LENGTH {
+			return invalid(&format!("name must be at most {MAX_NAME_LENGTH} bytes long"));
+		}
+		if !(self.swing_time.is_finite() && self.swing_time > 0.0) {
+			return invalid("swing_time must be greater than zero");
+		}
+		if !(self.arc.is_finite() && self.arc > 0.0) {
+			return invalid("arc must be greater than zero");
+		}
+		if !(self.windup.max.is_finite() && self.windup.max >= 0.0 && self.windup.rate.is_finite() && self.windup.rate > 0.0 && self.windup.angle.is_finite()) {
+			return invalid("windup must have a positive rate and a max of at least zero");
+		}
+		if !(self.knockback.is_finite() && self.knockback >= 0.0) {
+			return invalid("knockback must be at least zero");
+		}
+		if !(self.blade.start.is_finite() && self.blade.end.is_finite() && self.blade.start != self.blade.end) {
+			return invalid("blade must start and end in different places");
+		}
+		if !(self.blade.radius.is_finite() && self.blade.radius > 0.0) {
+			return invalid("blade radius must be greater than zero");
+		}
+		if !(self.sprite_size.is_finite() && self.sprite_offset.is_finite()) {
+			return invalid("sprite_size and sprite_offset must be numbers");
+		}
+		Ok(())
+	}
+}
+
+#[derive(Default)]
+struct WeaponSetLoader;
+
+impl AssetLoader for WeaponSetLoader {
+	type Asset = WeaponSet;
+	type Settings = ();
+	type Error = WeaponSetError;
+
+	async fn load(
+		&self,
+		reader: &mut dyn Reader,
+		_settings: &(),
+		_load_context: &mut LoadContext<'_>,
+	) -> Result<Self::Asset, Self::Error> {
+		let mut bytes = Vec::new();
+		reader.read_to_end(&mut bytes).await?;
+		let set = ron::de::from_bytes::<WeaponSet>(&bytes)?;
+
+		if set.weapons.is_empty() {
+			return Err(WeaponSetError::NoWeapons);
+		}
+		let mut names = HashSet::new();
+		for (index, weapon) in set.weapons.iter().enumerate() {
+			if !names.insert(weapon.name.as_str()) {
+				return Err(WeaponSetError::Duplicate { index, name: weapon.name.clone() });
+			}
+			weapon.validate()?;
+		}
+		Ok(set)
+	}
+
+	fn extensions(&self) -> &[&str] {
+		&["weapons.ron"]
+	}
+}
+
+pub fn choose_starting_weapon(
+	mut loadout: ResMut<Loadout>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+) {
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	let name = loadout.next.take().unwrap_or_else(|| loadout.chosen.clone());
+	loadout.current = weapons.get_or_default(&name).name.clone();
+}
+
+#[derive(Component)]
+pub struct Pickup(String);
+
+/// Wave whose pickups are spawned
+#[derive(Resource)]
+pub struct PickupWave(usize);
+
+/// Lays out the pickups of the current wave whenever it changes, clearing any left from the last one
+pub fn update_wave_pickups(
+	mut commands: Commands,
+	spawn_tracker: Res<SpawnTracker>,
+	pickup_wave: Option<Res<PickupWave>>,
+	pickup_query: Query<Entity, With<Pickup>>,
+	levels: Res<Assets<Level>>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+	textures: Res<TextureAssets>,
+	asset_server: Res<AssetServer>,
+) {
+	if pickup_wave.is_some_and(|pickup_wave| pickup_wave.0 == spawn_tracker.wave()) {
+		return;
+	}
+	for entity in pickup_query.iter() {
+		commands.entity(entity).despawn();
+	}
+	let level = levels.get(spawn_tracker.level()).expect("Could not find level");
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	for record in level.waves[spawn_tracker.wave()].pickups.iter() {
+		let Some(weapon) = weapons.get(&record.weapon) else {
+			warn!("Wave {} has a pickup for {}, which isn't a weapon", spawn_tracker.wave(), record.weapon);
+			continue;
+		};
+		commands.spawn((
+			Sprite {
+				image: textures.hoop.clone(),
+				custom_size: Some(Vec2::splat(PICKUP_RADIUS * 2.0)),
+				..default()
+			},
+			Transform::from_translation(record.position.extend(0.5)),
+			Pickup(weapon.name.clone()),
+		)).with_child((
+			Sprite {
+				image: asset_server.load(&weapon.sprite),
+				custom_size: Some(weapon.sprite_size * 0.5),
+				..default()
+			},
+			Transform::from_rotation(Quat::from_rotation_z(-std::f32::consts::FRAC_PI_4)),
+		));
+	}
+	commands.insert_resource(PickupWave(spawn_tracker.wave()));
+}
+
+/// Swaps the player's weapon for any pickup they walk over, though not in the middle of a swing
+pub fn pick_up_weapons(
+	mut commands: Commands,
+	player_query: Query<&Transform, With<PlayerInfo>>,
+	pickup_query: Query<(Entity, &Pickup, &Transform), Without<PlayerInfo>>,
+	mut weapon_query: Query<&mut WeaponPivot>,
+	mut weapon_sprite_query: Query<(&mut Sprite, &mut Transform), (With<WeaponSprite>, Without<PlayerInfo>, Without<Pickup>)>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+	asset_server: Res<AssetServer>,
+) {
+	let p_transform = player_query.single().expect("Could not find player");
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	for (entity, Pickup(name), transform) in pickup_query.iter() {
+		if transform.translation.xy().distance(p_transform.translation.xy()) > PICKUP_RADIUS {
+			continue;
+		}
+		let Ok(mut weapon_pivot) = weapon_query.single_mut() else {continue};
+		if weapon_pivot.swinging() {
+			continue;
+		}
+		let weapon = weapons.get_or_default(name);
+		weapon_pivot.equip(weapon.clone());
+		for (mut sprite, mut w_transform) in weapon_sprite_query.iter_mut() {
+			sprite.image = asset_server.load(&weapon.sprite);
+			sprite.custom_size = Some(weapon.sprite_size);
+			w_transform.translation = weapon.sprite_offset.extend(0.0);
+		}
+		commands.entity(entity).despawn();
+	}
+}
+
+fn cleanup_pickups(mut commands: Commands, pickup_query: Query<Entity, With<Pickup>>) {
+	for entity in pickup_query.iter() {
+		commands.entity(entity).despawn();
+	}
+	commands.remove_resource::<PickupWave>();
+}
+
+#[derive(Component)]
+struct WeaponButton;
+
+#[derive(Component)]
+struct WeaponButtonLabel;
+
+fn weapon_label(loadout: &Loadout, weapons: &WeaponSet) -> String {
+	format!("WEAPON: {}", weapons.get_or_default(&loadout.chosen).name.to_uppercase())
+}
+
+/// Sits above the play button on the menu, each click moves on to the next weapon
+fn spawn_weapon_button(
+	mut commands: Commands,
+	loadout: Res<Loadout>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+) {
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	commands.spawn(button(
+		Node {
+			position_type: PositionType::Absolute,
+			left: Val::Percent(50.0),
+			top: Val::Percent(85.0),
+			width: Val::Px(262.0),
+			height: Val::Px(40.0),
+			margin: UiRect {
+				left: Val::Px(-131.0),
+				top: Val::Px(-85.0),
+				..default()
+			},
+			..default()
+		},
+		WeaponButton,
+	)).with_child((button_label(weapon_label(&loadout, weapons), 22.0), WeaponButtonLabel));
+}
+
+fn click_weapon_button(
+	mut loadout: ResMut<Loadout>,
+	data: Res<DataAssets>,
+	weapon_sets: Res<Assets<WeaponSet>>,
+	mut interaction_query: Query<(&Interaction, &mut BackgroundColor, &ButtonColors), (Changed<Interaction>, With<WeaponButton>)>,
+	mut label_query: Query<&mut Text, With<WeaponButtonLabel>>,
+) {
+	let weapons = weapon_sets.get(&data.weapons).expect("Could not find weapons");
+	for (interaction, mut color, button_colors) in &mut interaction_query {
+		match *interaction {
+			Interaction::Pressed => {
+				let current = weapons.get_or_default(&loadout.chosen).name.clone();
+				loadout.chosen = weapons.after(&current).name.clone();
+				for mut text in label_query.iter_mut() {
+					text.0 = weapon_label(&loadout, weapons);
+				}
+			}
+			Interaction::Hovered => {
+				*color = button_colors.hovered.into();
+			}
+			Interaction::None => {
+				*color = button_colors.normal.into();
+			}
+		}
+	}
+}
+
+fn cleanup_weapon_button(mut commands: Commands, button_query: Query<Entity, With<WeaponButton>>) {
+	for entity in button_query.iter() {
+		commands.entity(entity).despawn();
+	}
+}